@@ -1,29 +1,45 @@
 use common::{
-    vesting::{InstantiateMsg as VestingInstantiateMsg, StakingInfo, VestingSchedule},
-    vesting_factory::{ExecuteMsg, InstantiateMsg, QueryMsg, VestingContractResponse},
+    error::ContractError,
+    vesting::{
+        BeneficiaryShare, ExecuteMsg as VestingExecuteMsg, InstantiateMsg as VestingInstantiateMsg,
+        QueryMsg as VestingQueryMsg, SolvencyResponse, StakingInfo, VestingSchedule,
+    },
+    vesting_factory::{
+        ContractSolvencyResponse, ExecuteMsg, InstantiateMsg, PendingStartsResponse, QueryMsg,
+        SolvenciesResponse, VestingContractResponse,
+    },
+    vesting_pool::ExecuteMsg as VestingPoolExecuteMsg,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
-    StdResult, SubMsg, WasmMsg,
+    to_binary, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Reply, Response,
+    StdError, StdResult, SubMsg, WasmMsg,
 };
+use cw_storage_plus::Bound;
 use protobuf::Message;
 
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{Config, CONFIG, TMP_STORE, VESTING_CONTRACTS};
+use crate::state::{Config, TmpStore, CONFIG, PENDING_STARTS, TMP_STORE, VESTING_CONTRACTS};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // validate addresses
     deps.api.addr_validate(&msg.bluna_token)?;
     deps.api.addr_validate(&msg.hub_contract)?;
     deps.api.addr_validate(&msg.reward_contract)?;
+    if let Some(vesting_pool) = &msg.vesting_pool {
+        deps.api.addr_validate(vesting_pool)?;
+    }
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
 
     CONFIG.save(
         deps.storage,
@@ -32,6 +48,8 @@ pub fn instantiate(
             hub_contract: msg.hub_contract,
             reward_contract: msg.reward_contract,
             vesting_contract_code_id: msg.vesting_contract_code_id,
+            vesting_pool: msg.vesting_pool,
+            admin: admin.to_string(),
         },
     )?;
 
@@ -39,12 +57,19 @@ pub fn instantiate(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::CreateVestingContract {
             owner_address,
             enable_staking,
             vesting_schedule,
+            beneficiaries,
+            deferred_start,
         } => create_vesting_contract(
             deps,
             env,
@@ -52,17 +77,34 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             owner_address,
             enable_staking,
             vesting_schedule,
+            beneficiaries,
+            deferred_start.unwrap_or(false),
         ),
         ExecuteMsg::ChangeVestingOwner { new_owner } => change_vesting_owner(deps, info, new_owner),
+        ExecuteMsg::StartVesting { limit } => start_vesting(deps, info, limit),
+        ExecuteMsg::Suspend { owner_address } => execute_vesting_contract(
+            deps,
+            info,
+            owner_address,
+            "suspend",
+            VestingExecuteMsg::Suspend {},
+        ),
+        ExecuteMsg::Resume { owner_address } => execute_vesting_contract(
+            deps,
+            info,
+            owner_address,
+            "resume",
+            VestingExecuteMsg::Resume {},
+        ),
     }
 }
 
 /// This will check converted bluna amount and set
 /// the amount as vesting amount.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     if msg.id != 1 {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::UnknownReplyId { id: msg.id });
     }
 
     let res: MsgInstantiateContractResponse =
@@ -70,13 +112,17 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
             StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
         })?;
 
-    let owner_address = TMP_STORE.load(deps.storage)?;
+    let tmp_store = TMP_STORE.load(deps.storage)?;
+    let owner_address = tmp_store.owner_address;
     let vesting_contract = res.get_contract_address();
     VESTING_CONTRACTS.save(
         deps.storage,
         owner_address.to_string(),
         &vesting_contract.to_string(),
     )?;
+    if tmp_store.deferred_start {
+        PENDING_STARTS.save(deps.storage, vesting_contract.to_string(), &Empty {})?;
+    }
     TMP_STORE.remove(deps.storage);
 
     Ok(Response::new().add_attributes(vec![
@@ -86,6 +132,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_vesting_contract(
     deps: DepsMut,
     env: Env,
@@ -93,16 +140,51 @@ fn create_vesting_contract(
     owner_address: String,
     enable_staking: bool,
     vesting_schedule: VestingSchedule,
-) -> StdResult<Response> {
+    beneficiaries: Option<Vec<BeneficiaryShare>>,
+    deferred_start: bool,
+) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     if VESTING_CONTRACTS
         .may_load(deps.storage, owner_address.to_string())?
         .is_some()
     {
-        return Err(StdError::generic_err("already exist"));
+        return Err(ContractError::AlreadyExists {});
+    }
+
+    if let Some(vesting_pool) = config.vesting_pool {
+        if beneficiaries.is_some() {
+            return Err(ContractError::BeneficiariesNotSupported {});
+        }
+        if deferred_start {
+            return Err(ContractError::DeferredStartNotSupported {});
+        }
+
+        VESTING_CONTRACTS.save(deps.storage, owner_address.to_string(), &vesting_pool)?;
+
+        return Ok(Response::new()
+            .add_message(WasmMsg::Execute {
+                contract_addr: vesting_pool.to_string(),
+                msg: to_binary(&VestingPoolExecuteMsg::CreateAccount {
+                    owner_address: owner_address.to_string(),
+                    enable_staking,
+                    vesting_schedule,
+                })?,
+                funds: info.funds,
+            })
+            .add_attributes(vec![
+                ("action", "create_vesting_account"),
+                ("owner_address", &owner_address),
+                ("vesting_contract", &vesting_pool),
+            ]));
     }
 
-    TMP_STORE.save(deps.storage, &owner_address)?;
+    TMP_STORE.save(
+        deps.storage,
+        &TmpStore {
+            owner_address: owner_address.to_string(),
+            deferred_start,
+        },
+    )?;
 
     Ok(Response::new().add_submessage(SubMsg::reply_on_success(
         CosmosMsg::Wasm(WasmMsg::Instantiate {
@@ -112,6 +194,14 @@ fn create_vesting_contract(
                 owner_address,
                 enable_staking,
                 vesting_schedule,
+                beneficiaries,
+                funder: Some(info.sender.to_string()),
+                start_trigger: if deferred_start {
+                    Some(env.contract.address.to_string())
+                } else {
+                    None
+                },
+                admin: Some(env.contract.address.to_string()),
                 staking_info: if enable_staking {
                     Some(StakingInfo {
                         bluna_token: config.bluna_token,
@@ -133,12 +223,12 @@ fn change_vesting_owner(
     deps: DepsMut,
     info: MessageInfo,
     new_owner: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let owner_address = info.sender;
     let vesting_contract_address =
         VESTING_CONTRACTS.may_load(deps.storage, owner_address.to_string())?;
     if vesting_contract_address.is_none() {
-        return Err(StdError::generic_err("vesting contract not found"));
+        return Err(ContractError::VestingContractNotFound {});
     }
 
     let vesting_contract_address = vesting_contract_address.unwrap();
@@ -156,13 +246,90 @@ fn change_vesting_owner(
     ]))
 }
 
+/// Start the first `limit` pending vesting contracts
+fn start_vesting(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let vesting_contracts = PENDING_STARTS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .map(String::from_utf8)
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(StdError::from)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for vesting_contract in vesting_contracts.iter() {
+        PENDING_STARTS.remove(deps.storage, vesting_contract.to_string());
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: vesting_contract.to_string(),
+                msg: to_binary(&VestingExecuteMsg::StartVesting {})?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "start_vesting"),
+        ("started", &vesting_contracts.len().to_string()),
+    ]))
+}
+
+/// Forward an admin operation to the vesting contract of the owner
+fn execute_vesting_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner_address: String,
+    action: &str,
+    msg: VestingExecuteMsg,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let vesting_contract = VESTING_CONTRACTS
+        .may_load(deps.storage, owner_address.to_string())?
+        .ok_or(ContractError::VestingContractNotFound {})?;
+    if config.vesting_pool.as_ref() == Some(&vesting_contract) {
+        return Err(ContractError::SuspensionNotSupported {});
+    }
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: vesting_contract.to_string(),
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        })
+        .add_attributes(vec![
+            ("action", action),
+            ("owner_address", &owner_address),
+            ("vesting_contract", &vesting_contract),
+        ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    Ok(match msg {
         QueryMsg::VestingContract { owner_address } => {
             to_binary(&vesting_contract(deps, owner_address)?)
         }
-    }
+        QueryMsg::Solvency { start_after, limit } => {
+            to_binary(&solvencies(deps, start_after, limit)?)
+        }
+        QueryMsg::PendingStarts { start_after, limit } => {
+            to_binary(&pending_starts(deps, start_after, limit)?)
+        }
+    }?)
 }
 
 fn vesting_contract(deps: Deps, owner_address: String) -> StdResult<VestingContractResponse> {
@@ -173,3 +340,64 @@ fn vesting_contract(deps: Deps, owner_address: String) -> StdResult<VestingContr
         vesting_contract,
     })
 }
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn solvencies(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SolvenciesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let contracts = VESTING_CONTRACTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (owner_address, vesting_contract) = item?;
+            // the pool holds many accounts, so it has no solvency of its own
+            let mut solvency: Option<SolvencyResponse> = None;
+            let mut error: Option<String> = None;
+            if config.vesting_pool.as_ref() != Some(&vesting_contract) {
+                // a failing contract, e.g. of an older code without the query,
+                // must not hide the others
+                match deps
+                    .querier
+                    .query_wasm_smart(&vesting_contract, &VestingQueryMsg::Solvency {})
+                {
+                    Ok(response) => solvency = Some(response),
+                    Err(err) => error = Some(err.to_string()),
+                }
+            }
+
+            Ok(ContractSolvencyResponse {
+                owner_address: String::from_utf8(owner_address)?,
+                vesting_contract,
+                solvency,
+                error,
+            })
+        })
+        .collect::<StdResult<Vec<ContractSolvencyResponse>>>()?;
+
+    Ok(SolvenciesResponse { contracts })
+}
+
+fn pending_starts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PendingStartsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let vesting_contracts = PENDING_STARTS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(String::from_utf8)
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(PendingStartsResponse { vesting_contracts })
+}
@@ -1,3 +1,9 @@
 pub mod contract;
 mod response;
 mod state;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(test)]
+mod mock_querier;
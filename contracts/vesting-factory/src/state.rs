@@ -1,11 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cosmwasm_std::Empty;
 use cw_storage_plus::{Item, Map};
 
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const TMP_STORE: Item<String> = Item::new("tmp_store");
+pub const TMP_STORE: Item<TmpStore> = Item::new("tmp_store");
 pub const VESTING_CONTRACTS: Map<String, String> = Map::new("vesting_contracts");
+/// Deferred vesting contracts not started yet
+pub const PENDING_STARTS: Map<String, Empty> = Map::new("pending_starts");
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
@@ -13,4 +16,13 @@ pub struct Config {
     pub hub_contract: String,
     pub reward_contract: String,
     pub vesting_contract_code_id: u64,
+    pub vesting_pool: Option<String>,
+    pub admin: String,
+}
+
+/// Instantiation in flight, read back at reply
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TmpStore {
+    pub owner_address: String,
+    pub deferred_start: bool,
 }
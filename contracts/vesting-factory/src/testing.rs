@@ -0,0 +1,303 @@
+use crate::contract::{execute, instantiate, query, reply};
+use crate::mock_querier::{mock_dependencies_with_querier, WasmMockQuerier};
+use crate::response::MsgInstantiateContractResponse;
+use common::error::ContractError;
+use common::vesting::{ExecuteMsg as VestingExecuteMsg, SolvencyResponse, VestingSchedule};
+use common::vesting_factory::{
+    ContractSolvencyResponse, ExecuteMsg, InstantiateMsg, PendingStartsResponse, QueryMsg,
+    SolvenciesResponse,
+};
+
+use cosmwasm_std::{
+    from_binary,
+    testing::{mock_env, mock_info, MockApi, MockStorage},
+    to_binary, Coin, ContractResult, CosmosMsg, DepsMut, OwnedDeps, Reply, StdError,
+    SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::Denom;
+use protobuf::Message;
+
+fn init(deps: DepsMut) {
+    let msg = InstantiateMsg {
+        bluna_token: "bluna0000".to_string(),
+        hub_contract: "hub0000".to_string(),
+        reward_contract: "reward0000".to_string(),
+        vesting_contract_code_id: 1,
+        vesting_pool: None,
+        admin: None,
+    };
+
+    instantiate(deps, mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+}
+
+/// Create the vesting contract of the owner and reply with its address
+fn create_vesting_contract(
+    deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    owner_address: &str,
+    vesting_contract: &str,
+    deferred_start: bool,
+) {
+    let msg = ExecuteMsg::CreateVestingContract {
+        owner_address: owner_address.to_string(),
+        enable_staking: false,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        deferred_start: Some(deferred_start),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, 1);
+
+    let mut data = MsgInstantiateContractResponse::new();
+    data.set_contract_address(vesting_contract.to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.write_to_bytes().unwrap().into()),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+}
+
+fn solvency(balance: u128, obligation: u128) -> SolvencyResponse {
+    SolvencyResponse {
+        vesting_denom: Denom::Native("uluna".to_string()),
+        balance: Uint128::new(balance),
+        obligation: Uint128::new(obligation),
+        surplus: Uint128::new(balance.saturating_sub(obligation)),
+        deficit: Uint128::new(obligation.saturating_sub(balance)),
+    }
+}
+
+#[test]
+fn solvency_pages() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    init(deps.as_mut());
+
+    create_vesting_contract(&mut deps, "owner0001", "vesting0001", false);
+    create_vesting_contract(&mut deps, "owner0002", "vesting0002", false);
+    create_vesting_contract(&mut deps, "owner0003", "vesting0003", false);
+    deps.querier
+        .with_solvency("vesting0001", solvency(1000000, 1000000));
+    deps.querier
+        .with_solvency("vesting0003", solvency(400000, 1000000));
+
+    // vesting0002 fails the query, which must not fail the page
+    let res: SolvenciesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Solvency {
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let error =
+        StdError::generic_err("Querier contract error: vesting0002 does not support the query");
+    assert_eq!(
+        res,
+        SolvenciesResponse {
+            contracts: vec![
+                ContractSolvencyResponse {
+                    owner_address: "owner0001".to_string(),
+                    vesting_contract: "vesting0001".to_string(),
+                    solvency: Some(solvency(1000000, 1000000)),
+                    error: None,
+                },
+                ContractSolvencyResponse {
+                    owner_address: "owner0002".to_string(),
+                    vesting_contract: "vesting0002".to_string(),
+                    solvency: None,
+                    error: Some(error.to_string()),
+                },
+            ]
+        }
+    );
+
+    let res: SolvenciesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Solvency {
+                start_after: Some("owner0002".to_string()),
+                limit: Some(2),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SolvenciesResponse {
+            contracts: vec![ContractSolvencyResponse {
+                owner_address: "owner0003".to_string(),
+                vesting_contract: "vesting0003".to_string(),
+                solvency: Some(solvency(400000, 1000000)),
+                error: None,
+            }]
+        }
+    );
+}
+
+#[test]
+fn deferred_start() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    init(deps.as_mut());
+
+    create_vesting_contract(&mut deps, "owner0001", "vesting0001", true);
+    create_vesting_contract(&mut deps, "owner0002", "vesting0002", false);
+    create_vesting_contract(&mut deps, "owner0003", "vesting0003", true);
+    create_vesting_contract(&mut deps, "owner0004", "vesting0004", true);
+
+    let pending_starts = |deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>| {
+        let res: PendingStartsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PendingStarts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.vesting_contracts
+    };
+    assert_eq!(
+        pending_starts(&deps),
+        vec!["vesting0001", "vesting0003", "vesting0004"]
+    );
+
+    let res: PendingStartsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingStarts {
+                start_after: Some("vesting0001".to_string()),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.vesting_contracts, vec!["vesting0003"]);
+
+    // only the admin can start
+    let msg = ExecuteMsg::StartVesting { limit: Some(2) };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let start_msg = |vesting_contract: &str| -> CosmosMsg {
+        WasmMsg::Execute {
+            contract_addr: vesting_contract.to_string(),
+            msg: to_binary(&VestingExecuteMsg::StartVesting {}).unwrap(),
+            funds: vec![],
+        }
+        .into()
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![start_msg("vesting0001"), start_msg("vesting0003")]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![("action", "start_vesting"), ("started", "2")]
+    );
+    assert_eq!(pending_starts(&deps), vec!["vesting0004"]);
+
+    let msg = ExecuteMsg::StartVesting { limit: None };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![start_msg("vesting0004")]
+    );
+    assert_eq!(pending_starts(&deps), Vec::<String>::new());
+}
+
+#[test]
+fn suspend_and_resume() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    init(deps.as_mut());
+
+    create_vesting_contract(&mut deps, "owner0001", "vesting0001", false);
+
+    let msg = ExecuteMsg::Suspend {
+        owner_address: "owner0001".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vesting0001".to_string(),
+            msg: to_binary(&VestingExecuteMsg::Suspend {}).unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "suspend"),
+            ("owner_address", "owner0001"),
+            ("vesting_contract", "vesting0001"),
+        ]
+    );
+
+    let msg = ExecuteMsg::Resume {
+        owner_address: "owner0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>(),
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vesting0001".to_string(),
+            msg: to_binary(&VestingExecuteMsg::Resume {}).unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(res.attributes[0], ("action", "resume"));
+
+    let msg = ExecuteMsg::Resume {
+        owner_address: "owner0002".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("admin0000", &[]), msg);
+    match res {
+        Err(ContractError::VestingContractNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -0,0 +1,598 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, Response, StdResult, SubMsg, Uint128, WasmMsg,
+};
+
+use common::error::ContractError;
+use common::events;
+use common::staking::{
+    query_exchange_rate, AccruedRewardsResponse, HubContractExecuteMsg, RewardContractExecuteMsg,
+    RewardContractQueryMsg,
+};
+use common::tax::deduct_tax;
+use common::vesting::{
+    claim_amount, transfer_message, LunaValueResponse, VestingAccount, VestingInfoResponse,
+    VestingSchedule,
+};
+use common::vesting_pool::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse, VestingAccountsResponse,
+};
+use cw20::{BalanceResponse, Cw20QueryMsg, Denom};
+use cw_storage_plus::Bound;
+
+use crate::state::{
+    Config, PendingAccount, RewardInfo, State, CONFIG, REWARD_INFOS, STATE, TMP_ACCOUNT,
+    VESTING_INFOS,
+};
+
+const VESTING_DENOM: &str = "uluna";
+const REWARDS_DENOM: &str = "uusd";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    deps.api.addr_validate(&msg.admin)?;
+    if let Some(staking_info) = &msg.staking_info {
+        deps.api.addr_validate(&staking_info.bluna_token)?;
+        deps.api.addr_validate(&staking_info.hub_contract)?;
+        deps.api.addr_validate(&staking_info.reward_contract)?;
+    }
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            admin: msg.admin,
+            staking_info: msg.staking_info,
+        },
+    )?;
+    STATE.save(deps.storage, &State::default())?;
+
+    Ok(Response::new())
+}
+
+/// This will check converted bluna amount and set
+/// the amount as vesting amount of the pending account.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != 1 {
+        return Err(ContractError::UnknownReplyId { id: msg.id });
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let staking_info = config.staking_info.clone().unwrap();
+    let pending = TMP_ACCOUNT.load(deps.storage)?;
+    TMP_ACCOUNT.remove(deps.storage);
+
+    let response: BalanceResponse = deps.querier.query_wasm_smart(
+        staking_info.bluna_token.to_string(),
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    let vesting_amount = response.balance.checked_sub(pending.bluna_balance)?;
+    let exchange_rate = query_exchange_rate(deps.as_ref(), &staking_info.hub_contract)?;
+
+    // rewards received before this account joins belong to the others
+    let mut state = update_reward_index(deps.as_ref(), &env, &config)?;
+    state.total_staked += vesting_amount;
+    STATE.save(deps.storage, &state)?;
+
+    let vesting_denom = Denom::Cw20(Addr::unchecked(staking_info.bluna_token));
+    VESTING_INFOS.save(
+        deps.storage,
+        pending.owner_address.to_string(),
+        &VestingAccount {
+            vesting_denom: vesting_denom.clone(),
+            vesting_amount,
+            vesting_schedule: pending.vesting_schedule,
+            claimed_amount: Uint128::zero(),
+            bond_exchange_rate: Some(exchange_rate),
+        },
+    )?;
+    REWARD_INFOS.save(
+        deps.storage,
+        pending.owner_address.to_string(),
+        &RewardInfo {
+            reward_index: state.reward_index,
+            pending_rewards: Uint128::zero(),
+            claimed_rewards: Uint128::zero(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_vesting_account"),
+            ("owner_address", &pending.owner_address),
+            ("vesting_amount", &vesting_amount.to_string()),
+        ])
+        .add_event(events::vesting_created(
+            env.contract.address.as_str(),
+            &pending.owner_address,
+            &vesting_denom,
+            vesting_amount,
+        )))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateAdmin { admin } => update_admin(deps, info, admin),
+        ExecuteMsg::CreateAccount {
+            owner_address,
+            enable_staking,
+            vesting_schedule,
+        } => create_account(
+            deps,
+            env,
+            info,
+            owner_address,
+            enable_staking,
+            vesting_schedule,
+        ),
+        ExecuteMsg::ChangeOwner { new_owner } => change_owner(deps, env, info, new_owner),
+        ExecuteMsg::Claim { recipient, amount } => claim(deps, env, info, recipient, amount),
+        ExecuteMsg::ClaimRewards { recipient } => claim_rewards(deps, env, info, recipient),
+    }
+}
+
+fn update_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    deps.api.addr_validate(&admin)?;
+    config.admin = admin.to_string();
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_admin"), ("admin", &admin)]))
+}
+
+fn create_account(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner_address: String,
+    enable_staking: bool,
+    vesting_schedule: VestingSchedule,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    deps.api.addr_validate(&owner_address)?;
+    if VESTING_INFOS.has(deps.storage, owner_address.to_string()) {
+        return Err(ContractError::AlreadyExists {});
+    }
+
+    // deposit validation
+    if info.funds.len() != 1 || info.funds[0].denom != VESTING_DENOM {
+        return Err(ContractError::InvalidDeposit {
+            denom: VESTING_DENOM.to_string(),
+        });
+    }
+
+    // validate vesting schedule with vesting amount
+    let vesting_token = info.funds[0].clone();
+    vesting_schedule.validate(env.block.time.seconds(), vesting_token.amount)?;
+
+    if enable_staking {
+        let staking_info = config
+            .staking_info
+            .ok_or(ContractError::MissingStakingInfo {})?;
+
+        // the bonded amount is the bLUNA balance increase seen at reply
+        let response: BalanceResponse = deps.querier.query_wasm_smart(
+            staking_info.bluna_token.to_string(),
+            &Cw20QueryMsg::Balance {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        TMP_ACCOUNT.save(
+            deps.storage,
+            &PendingAccount {
+                owner_address,
+                vesting_schedule,
+                bluna_balance: response.balance,
+            },
+        )?;
+
+        return Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: staking_info.hub_contract,
+                msg: to_binary(&HubContractExecuteMsg::Bond {})?,
+                funds: info.funds,
+            },
+            1,
+        )));
+    }
+
+    let vesting_denom = Denom::Native(VESTING_DENOM.to_string());
+    VESTING_INFOS.save(
+        deps.storage,
+        owner_address.to_string(),
+        &VestingAccount {
+            vesting_denom: vesting_denom.clone(),
+            vesting_amount: vesting_token.amount,
+            vesting_schedule,
+            claimed_amount: Uint128::zero(),
+            bond_exchange_rate: None,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_vesting_account"),
+            ("owner_address", &owner_address),
+            ("vesting_amount", &vesting_token.amount.to_string()),
+        ])
+        .add_event(events::vesting_created(
+            env.contract.address.as_str(),
+            &owner_address,
+            &vesting_denom,
+            vesting_token.amount,
+        )))
+}
+
+fn load_vesting_info(deps: Deps, owner_address: &str) -> Result<VestingAccount, ContractError> {
+    VESTING_INFOS
+        .may_load(deps.storage, owner_address.to_string())?
+        .ok_or(ContractError::VestingAccountNotFound {})
+}
+
+/// Move the account of the sender to `new_owner`,
+/// including its pending staking rewards.
+fn change_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let owner_address = info.sender.to_string();
+    let vesting_info = load_vesting_info(deps.as_ref(), &owner_address)?;
+
+    deps.api.addr_validate(&new_owner)?;
+    if VESTING_INFOS.has(deps.storage, new_owner.to_string()) {
+        return Err(ContractError::AlreadyExists {});
+    }
+
+    if let Some(mut reward_info) = REWARD_INFOS.may_load(deps.storage, owner_address.to_string())? {
+        let config: Config = CONFIG.load(deps.storage)?;
+        let state = update_reward_index(deps.as_ref(), &env, &config)?;
+        STATE.save(deps.storage, &state)?;
+
+        reward_info.settle(state.reward_index, vesting_info.remaining_amount()?);
+        REWARD_INFOS.remove(deps.storage, owner_address.to_string());
+        REWARD_INFOS.save(deps.storage, new_owner.to_string(), &reward_info)?;
+    }
+
+    VESTING_INFOS.remove(deps.storage, owner_address.to_string());
+    VESTING_INFOS.save(deps.storage, new_owner.to_string(), &vesting_info)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "change_owner"),
+            ("new_owner", new_owner.as_str()),
+        ])
+        .add_event(events::owner_changed(
+            env.contract.address.as_str(),
+            &new_owner,
+            &owner_address,
+        )))
+}
+
+fn claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let owner_address = info.sender.to_string();
+    let mut vesting_info = load_vesting_info(deps.as_ref(), &owner_address)?;
+    let recipient = recipient.unwrap_or_else(|| owner_address.to_string());
+    deps.api.addr_validate(&recipient)?;
+
+    let vested_amount = vesting_info
+        .vesting_schedule
+        .vested_amount(env.block.time.seconds(), vesting_info.vesting_amount)?;
+    let claim_amount = claim_amount(
+        vesting_info.claimable_amount(env.block.time.seconds())?,
+        amount,
+    )?;
+
+    // settle before the account's stake in the pool shrinks by the claim
+    if let Some(mut reward_info) = REWARD_INFOS.may_load(deps.storage, owner_address.to_string())? {
+        let config: Config = CONFIG.load(deps.storage)?;
+        let mut state = update_reward_index(deps.as_ref(), &env, &config)?;
+        reward_info.settle(state.reward_index, vesting_info.remaining_amount()?);
+        REWARD_INFOS.save(deps.storage, owner_address.to_string(), &reward_info)?;
+
+        state.total_staked = state.total_staked.checked_sub(claim_amount)?;
+        STATE.save(deps.storage, &state)?;
+    }
+
+    vesting_info.claimed_amount = vesting_info.claimed_amount.checked_add(claim_amount)?;
+    VESTING_INFOS.save(deps.storage, owner_address.to_string(), &vesting_info)?;
+
+    let message = transfer_message(
+        &deps.querier,
+        &vesting_info.vesting_denom,
+        recipient.clone(),
+        claim_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            ("action", "claim"),
+            ("owner_address", owner_address.as_str()),
+            ("recipient", recipient.as_str()),
+        ])
+        .add_attributes(vec![
+            ("vesting_amount", &vesting_info.vesting_amount.to_string()),
+            ("vested_amount", &vested_amount.to_string()),
+            ("claim_amount", &claim_amount.to_string()),
+        ])
+        .add_event(events::vesting_claimed(
+            env.contract.address.as_str(),
+            &owner_address,
+            &vesting_info.vesting_denom,
+            &recipient,
+            claim_amount,
+        )))
+}
+
+fn claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let owner_address = info.sender.to_string();
+    let vesting_info = load_vesting_info(deps.as_ref(), &owner_address)?;
+    let mut reward_info = REWARD_INFOS
+        .may_load(deps.storage, owner_address.to_string())?
+        .ok_or(ContractError::StakingDisabled {})?;
+    let recipient = recipient.unwrap_or_else(|| owner_address.to_string());
+    deps.api.addr_validate(&recipient)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let staking_info = config.staking_info.clone().unwrap();
+    let mut state = update_reward_index(deps.as_ref(), &env, &config)?;
+    reward_info.settle(state.reward_index, vesting_info.remaining_amount()?);
+
+    let rewards_claim_amount = reward_info.pending_rewards;
+    if rewards_claim_amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    reward_info.pending_rewards = Uint128::zero();
+    reward_info.claimed_rewards += rewards_claim_amount;
+    REWARD_INFOS.save(deps.storage, owner_address.to_string(), &reward_info)?;
+
+    state.rewards_paid += rewards_claim_amount;
+    STATE.save(deps.storage, &state)?;
+
+    // withdraw accrued rewards of the pool when the balance is not enough
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let existing_rewards: Coin = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), REWARDS_DENOM)?;
+    if existing_rewards.amount < rewards_claim_amount {
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: staking_info.reward_contract,
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    messages.push(
+        BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![deduct_tax(
+                &deps.querier,
+                Coin {
+                    denom: REWARDS_DENOM.to_string(),
+                    amount: rewards_claim_amount,
+                },
+            )?],
+        }
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "claim_rewards"),
+            ("recipient", recipient.as_str()),
+            (
+                "rewards_claim_amount",
+                rewards_claim_amount.to_string().as_str(),
+            ),
+        ])
+        .add_event(events::rewards_claimed(
+            env.contract.address.as_str(),
+            &owner_address,
+            &Denom::Native(REWARDS_DENOM.to_string()),
+            &recipient,
+            rewards_claim_amount,
+        )))
+}
+
+/// Total rewards ever received by the pool, which is
+/// paid rewards + rewards balance + accrued rewards.
+fn total_rewards(deps: Deps, env: &Env, config: &Config, state: &State) -> StdResult<Uint128> {
+    let staking_info = match &config.staking_info {
+        Some(staking_info) => staking_info,
+        None => return Ok(state.rewards_paid),
+    };
+
+    let existing_rewards: Coin = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), REWARDS_DENOM)?;
+    let response: AccruedRewardsResponse = deps.querier.query_wasm_smart(
+        Addr::unchecked(staking_info.reward_contract.to_string()),
+        &RewardContractQueryMsg::AccruedRewards {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+
+    Ok(state.rewards_paid + existing_rewards.amount + response.rewards)
+}
+
+/// Load the pool state with `reward_index` moved forward
+/// to the current rewards. The caller stores the result.
+fn update_reward_index(deps: Deps, env: &Env, config: &Config) -> StdResult<State> {
+    let mut state: State = STATE.load(deps.storage)?;
+    let total_rewards = total_rewards(deps, env, config, &state)?;
+    state.update_reward_index(total_rewards)?;
+
+    Ok(state)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    Ok(match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps, env)?),
+        QueryMsg::VestingAccount { owner_address } => {
+            to_binary(&vesting_account(deps, &env, owner_address)?)
+        }
+        QueryMsg::VestingAccounts { start_after, limit } => {
+            to_binary(&vesting_accounts(deps, env, start_after, limit)?)
+        }
+    }?)
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        admin: config.admin,
+        staking_info: config.staking_info,
+    })
+}
+
+fn query_state(deps: Deps, env: Env) -> StdResult<StateResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let state = update_reward_index(deps, &env, &config)?;
+
+    Ok(StateResponse {
+        total_staked: state.total_staked,
+        reward_index: state.reward_index,
+    })
+}
+
+fn vesting_account(
+    deps: Deps,
+    env: &Env,
+    owner_address: String,
+) -> Result<VestingInfoResponse, ContractError> {
+    let vesting_info = load_vesting_info(deps, &owner_address)?;
+    Ok(vesting_info_response(
+        deps,
+        env,
+        owner_address,
+        vesting_info,
+    )?)
+}
+
+fn vesting_info_response(
+    deps: Deps,
+    env: &Env,
+    owner_address: String,
+    vesting_info: VestingAccount,
+) -> StdResult<VestingInfoResponse> {
+    let vested_amount = vesting_info
+        .vesting_schedule
+        .vested_amount(env.block.time.seconds(), vesting_info.vesting_amount)?;
+    let claimable_amount = vesting_info.claimable_amount(env.block.time.seconds())?;
+
+    // the principal is reported even when the hub or reward contract fails
+    let mut claimable_staking_rewards = Uint128::zero();
+    let mut luna_value: Option<LunaValueResponse> = None;
+    let mut rewards_error: Option<String> = None;
+    if let Some(mut reward_info) = REWARD_INFOS.may_load(deps.storage, owner_address.to_string())? {
+        let mut staking_status = || -> StdResult<(Uint128, LunaValueResponse)> {
+            let config: Config = CONFIG.load(deps.storage)?;
+            let state = update_reward_index(deps, env, &config)?;
+            reward_info.settle(state.reward_index, vesting_info.remaining_amount()?);
+
+            let staking_info = config.staking_info.unwrap();
+            let exchange_rate = query_exchange_rate(deps, &staking_info.hub_contract)?;
+            let luna_value = LunaValueResponse::new(
+                exchange_rate,
+                vesting_info.bond_exchange_rate.unwrap_or(exchange_rate),
+                vesting_info.vesting_amount,
+                vested_amount,
+                claimable_amount,
+            );
+
+            Ok((reward_info.pending_rewards, luna_value))
+        };
+
+        match staking_status() {
+            Ok((rewards, value)) => {
+                claimable_staking_rewards = rewards;
+                luna_value = Some(value);
+            }
+            Err(err) => rewards_error = Some(err.to_string()),
+        }
+    }
+
+    Ok(VestingInfoResponse {
+        owner_address,
+        vesting_denom: vesting_info.vesting_denom,
+        vesting_amount: vesting_info.vesting_amount,
+        vested_amount,
+        vesting_schedule: vesting_info.vesting_schedule,
+        claimable_amount,
+        claimable_staking_rewards,
+        luna_value,
+        rewards_error,
+    })
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn vesting_accounts(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VestingAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let vesting_accounts = VESTING_INFOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (owner_address, vesting_info) = item?;
+            vesting_info_response(deps, &env, String::from_utf8(owner_address)?, vesting_info)
+        })
+        .collect::<StdResult<Vec<VestingInfoResponse>>>()?;
+
+    Ok(VestingAccountsResponse { vesting_accounts })
+}
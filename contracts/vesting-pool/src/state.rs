@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use common::vesting::{StakingInfo, VestingAccount, VestingSchedule};
+use cosmwasm_std::{Decimal, StdResult, Uint128};
+use cw_storage_plus::{Item, Map};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const STATE: Item<State> = Item::new("state");
+pub const TMP_ACCOUNT: Item<PendingAccount> = Item::new("tmp_account");
+pub const VESTING_INFOS: Map<String, VestingAccount> = Map::new("vesting_infos");
+pub const REWARD_INFOS: Map<String, RewardInfo> = Map::new("reward_infos");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Config {
+    pub admin: String,
+    pub staking_info: Option<StakingInfo>,
+}
+
+/// State tracks staking rewards of the whole pool. `reward_index` is
+/// the rewards accumulated per staked bLUNA, and `rewards_checkpoint`
+/// is the part of the total rewards (paid + balance + accrued) indexed so far.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct State {
+    pub total_staked: Uint128,
+    pub reward_index: Decimal,
+    pub rewards_checkpoint: Uint128,
+    pub rewards_paid: Uint128,
+}
+
+impl State {
+    /// Move `reward_index` forward to `total_rewards`. The part lost to
+    /// rounding stays out of the checkpoint and is indexed next time.
+    /// Rewards received while nothing is staked are not assigned to anyone.
+    pub fn update_reward_index(&mut self, total_rewards: Uint128) -> StdResult<()> {
+        if self.total_staked.is_zero() {
+            self.rewards_checkpoint = total_rewards;
+            return Ok(());
+        }
+
+        let new_rewards = total_rewards.checked_sub(self.rewards_checkpoint)?;
+        let index_delta = Decimal::from_ratio(new_rewards, self.total_staked);
+        self.reward_index = self.reward_index + index_delta;
+        self.rewards_checkpoint += index_delta * self.total_staked;
+        Ok(())
+    }
+}
+
+/// RewardInfo exists only for staking enabled accounts
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardInfo {
+    pub reward_index: Decimal,
+    pub pending_rewards: Uint128,
+    pub claimed_rewards: Uint128,
+}
+
+impl RewardInfo {
+    /// Add what `staked_amount` earned since the last settled index
+    pub fn settle(&mut self, reward_index: Decimal, staked_amount: Uint128) {
+        self.pending_rewards += (reward_index - self.reward_index) * staked_amount;
+        self.reward_index = reward_index;
+    }
+}
+
+/// Staking enabled account waiting for bonded bLUNA at reply
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingAccount {
+    pub owner_address: String,
+    pub vesting_schedule: VestingSchedule,
+    pub bluna_balance: Uint128,
+}
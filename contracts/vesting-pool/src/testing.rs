@@ -0,0 +1,561 @@
+use crate::contract::{execute, instantiate, query, reply};
+use common::error::ContractError;
+use common::mock_querier::mock_dependencies_with_querier;
+use common::staking::{HubContractExecuteMsg, RewardContractExecuteMsg};
+use common::vesting::{StakingInfo, VestingInfoResponse, VestingSchedule};
+use common::vesting_pool::{
+    ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse, VestingAccountsResponse,
+};
+
+use cosmwasm_std::{
+    from_binary,
+    testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR},
+    to_binary, Addr, BankMsg, Coin, ContractResult, Decimal, Event, Reply, SubMsg,
+    SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Denom};
+
+fn vesting_schedule() -> VestingSchedule {
+    VestingSchedule {
+        start_time: "105".to_string(),
+        end_time: "110".to_string(),
+        vesting_interval: "5".to_string(),
+    }
+}
+
+fn bond_reply() -> Reply {
+    Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    }
+}
+
+#[test]
+fn create_and_claim_native() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        admin: "factory0000".to_string(),
+        staking_info: None,
+    };
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let funds = [Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000),
+    }];
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0000".to_string(),
+        enable_staking: false,
+        vesting_schedule: vesting_schedule(),
+    };
+
+    // only the admin can create accounts
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
+    )
+    .unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "create_vesting_account"),
+            ("owner_address", "owner0000"),
+            ("vesting_amount", "1000000"),
+        ]
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_created").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0000"),
+            ("denom_kind", "native"),
+            ("denom", "uluna"),
+            ("amount", "1000000"),
+        ])]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap_err();
+    match res {
+        ContractError::AlreadyExists {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        vesting_schedule: vesting_schedule(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    match res {
+        ContractError::VestingAccountNotFound {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(500000u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_claimed").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0000"),
+            ("denom_kind", "native"),
+            ("denom", "uluna"),
+            ("address", "owner0000"),
+            ("amount", "500000"),
+        ])]
+    );
+
+    // accounts are independent from each other
+    assert_eq!(
+        from_binary::<VestingAccountsResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestingAccounts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .vesting_accounts
+        .into_iter()
+        .map(|v| (v.owner_address, v.claimable_amount))
+        .collect::<Vec<_>>(),
+        vec![
+            ("owner0000".to_string(), Uint128::zero()),
+            ("owner0001".to_string(), Uint128::new(500000u128)),
+        ]
+    );
+
+    // move the account to a new owner
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0001".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+    match res {
+        ContractError::AlreadyExists {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::VestingAccount {
+            owner_address: "owner0000".to_string(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::VestingAccountNotFound {}));
+
+    env.block.time = Timestamp::from_seconds(110);
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::VestingAccount {
+                    owner_address: "owner0002".to_string(),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0002".to_string(),
+            vesting_denom: Denom::Native("uluna".to_string()),
+            vesting_amount: Uint128::new(1000000u128),
+            vested_amount: Uint128::new(1000000u128),
+            vesting_schedule: vesting_schedule(),
+            claimable_amount: Uint128::new(500000u128),
+            claimable_staking_rewards: Uint128::zero(),
+            luna_value: None,
+            rewards_error: None,
+        }
+    );
+}
+
+#[test]
+fn pooled_staking_rewards() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        admin: "factory0000".to_string(),
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+    };
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // first account bonds 1000 bLUNA
+    let funds = [Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000),
+    }];
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0000".to_string(),
+        enable_staking: true,
+        vesting_schedule: vesting_schedule(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_binary(&HubContractExecuteMsg::Bond {}).unwrap(),
+                funds: funds.to_vec(),
+            },
+            1
+        )]
+    );
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let res = reply(deps.as_mut(), env.clone(), bond_reply()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "create_vesting_account"),
+            ("owner_address", "owner0000"),
+            ("vesting_amount", "1000"),
+        ]
+    );
+
+    // 100 rewards go to the first account only
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100u128))]);
+
+    // second account bonds 2000 bLUNA
+    let funds = [Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(2000),
+    }];
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        vesting_schedule: vesting_schedule(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap();
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(3000))]);
+    let _res = reply(deps.as_mut(), env.clone(), bond_reply()).unwrap();
+
+    // 300 more rewards are split by staked amount
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(400u128))]);
+    assert_eq!(
+        from_binary::<StateResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::State {}).unwrap()
+        )
+        .unwrap(),
+        StateResponse {
+            total_staked: Uint128::new(3000),
+            reward_index: Decimal::from_ratio(2u64, 10u64),
+        }
+    );
+
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(200u128),
+                }],
+            }),
+        ]
+    );
+
+    // the pool now holds the withdrawn rewards minus the payout
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(200u128),
+        }],
+    );
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
+
+    // half vested, and the claimed half leaves the pool stake
+    env.block.time = Timestamp::from_seconds(105);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner0000".to_string(),
+                amount: Uint128::new(500u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    // 250 more rewards, 500 for the first account and 2000 for the second
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(250u128))]);
+
+    let res = from_binary::<VestingAccountsResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VestingAccounts {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.vesting_accounts
+            .into_iter()
+            .map(|v| (
+                v.owner_address,
+                v.vesting_denom,
+                v.claimable_staking_rewards
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "owner0000".to_string(),
+                Denom::Cw20(Addr::unchecked("bluna")),
+                Uint128::new(50u128)
+            ),
+            (
+                "owner0001".to_string(),
+                Denom::Cw20(Addr::unchecked("bluna")),
+                Uint128::new(400u128)
+            ),
+        ]
+    );
+
+    // rewards are paid with the stability tax deducted
+    deps.querier.with_tax(
+        Decimal::permille(5),
+        &[(&"uusd".to_string(), &Uint128::new(1000000u128))],
+    );
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let res = execute(deps.as_mut(), env, mock_info("owner0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    // 400 / 1.005
+                    amount: Uint128::new(398u128),
+                }],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn claim_errors() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        admin: "factory0000".to_string(),
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+    };
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let funds = [Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000),
+    }];
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0000".to_string(),
+        enable_staking: true,
+        vesting_schedule: vesting_schedule(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap();
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
+    let _res = reply(deps.as_mut(), env.clone(), bond_reply()).unwrap();
+
+    let msg = ExecuteMsg::CreateAccount {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        vesting_schedule: vesting_schedule(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory0000", &funds),
+        msg,
+    )
+    .unwrap();
+
+    // nothing vested yet
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+    match res {
+        ContractError::NothingToClaim {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the vested amount can be claimed
+    env.block.time = Timestamp::from_seconds(105);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: Some(Uint128::new(501)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+    match res {
+        ContractError::ClaimAmountExceeded {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // others can not claim the rewards of an account
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: Some("addr0000".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    match res {
+        ContractError::VestingAccountNotFound {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // no rewards received yet
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap_err();
+    match res {
+        ContractError::NothingToClaim {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the account does not stake
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let res = execute(deps.as_mut(), env, mock_info("owner0001", &[]), msg).unwrap_err();
+    match res {
+        ContractError::StakingDisabled {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
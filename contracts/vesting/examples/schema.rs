@@ -1,7 +1,12 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use common::vesting::{ExecuteMsg, InstantiateMsg, QueryMsg, VestingInfoResponse};
+use common::vesting::{
+    BeneficiariesResponse, BeneficiaryResponse, ClaimHistoryResponse, ConfigResponse, ExecuteMsg,
+    InstantiateMsg, NextUnlockResponse, OperatorsResponse, QueryMsg, RewardsResponse,
+    SolvencyResponse, SuspensionsResponse, UnlockScheduleResponse, VestedAmountResponse,
+    VestingInfoAtResponse, VestingInfoResponse,
+};
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 fn main() {
@@ -14,4 +19,16 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(VestingInfoResponse), &out_dir);
+    export_schema(&schema_for!(OperatorsResponse), &out_dir);
+    export_schema(&schema_for!(BeneficiaryResponse), &out_dir);
+    export_schema(&schema_for!(BeneficiariesResponse), &out_dir);
+    export_schema(&schema_for!(SolvencyResponse), &out_dir);
+    export_schema(&schema_for!(VestingInfoAtResponse), &out_dir);
+    export_schema(&schema_for!(VestedAmountResponse), &out_dir);
+    export_schema(&schema_for!(UnlockScheduleResponse), &out_dir);
+    export_schema(&schema_for!(NextUnlockResponse), &out_dir);
+    export_schema(&schema_for!(ClaimHistoryResponse), &out_dir);
+    export_schema(&schema_for!(RewardsResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(SuspensionsResponse), &out_dir);
 }
@@ -0,0 +1,242 @@
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+use protobuf::CodedOutputStream;
+
+use common::error::ContractError;
+use common::staking::{query_exchange_rate, HubContractExecuteMsg};
+use common::tax::deduct_tax;
+use common::vesting::CompoundConfig;
+use cw20::{BalanceResponse, Cw20QueryMsg};
+
+use crate::contract::{check_permission, REWARDS_DENOM, VESTING_DENOM};
+use crate::external::handle::{Asset, AssetInfo, PairExecuteMsg};
+use crate::rewards::{settle_owner_rewards, take_rewards};
+use crate::state::{Config, TmpSwap, CONFIG, TMP_COMPOUND, TMP_SWAP, VESTING_INFO};
+
+pub(crate) const SWAP_REPLY_ID: u64 = 2;
+pub(crate) const BOND_REPLY_ID: u64 = 3;
+
+/// Largest spread accepted from the pair contract, 1%
+const MAX_SPREAD: u64 = 10;
+
+pub(crate) fn set_compounding(
+    deps: DepsMut,
+    info: MessageInfo,
+    compounding: Option<CompoundConfig>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if compounding.is_some() && !config.staking_enabled {
+        return Err(ContractError::StakingDisabled {});
+    }
+
+    if let Some(pair_contract) = compounding.as_ref().and_then(|c| c.pair_contract.as_ref()) {
+        deps.api.addr_validate(pair_contract)?;
+    }
+
+    config.compounding = compounding;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "set_compounding"))
+}
+
+/// Swap pending rewards of the owner to uluna. The swapped uluna
+/// is bonded at `SWAP_REPLY_ID` reply and the received bLUNA is
+/// added to the vesting position at `BOND_REPLY_ID` reply.
+pub(crate) fn compound(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minimum_receive: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let compounding = match config.compounding.clone() {
+        Some(compounding) if config.staking_enabled => compounding,
+        _ => return Err(ContractError::CompoundingDisabled {}),
+    };
+
+    check_permission(deps.as_ref(), &env, &config, &info.sender, |p| p.staking)?;
+
+    let compound_amount = settle_owner_rewards(deps.branch(), &env, &config)?;
+    if compound_amount.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    messages.extend(take_rewards(
+        deps.branch(),
+        &env,
+        &config,
+        &config.owner_address,
+        compound_amount,
+    )?);
+
+    // uluna already held, e.g. sent by mistake, is left for Sweep
+    let uluna_balance = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), VESTING_DENOM)?
+        .amount;
+    TMP_SWAP.save(
+        deps.storage,
+        &TmpSwap {
+            uluna_balance,
+            minimum_receive,
+        },
+    )?;
+
+    let offer_coin = Coin {
+        denom: REWARDS_DENOM.to_string(),
+        amount: compound_amount,
+    };
+    let swap_message = match compounding.pair_contract {
+        Some(pair_contract) => {
+            // unlike the market swap, sending uusd to the pair is taxed
+            let offer_coin = deduct_tax(&deps.querier, offer_coin)?;
+            WasmMsg::Execute {
+                contract_addr: pair_contract,
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: offer_coin.denom.to_string(),
+                        },
+                        amount: offer_coin.amount,
+                    },
+                    belief_price: minimum_receive
+                        .filter(|amount| !amount.is_zero())
+                        .map(|amount| Decimal::from_ratio(offer_coin.amount, amount)),
+                    max_spread: Some(Decimal::permille(MAX_SPREAD)),
+                    to: None,
+                })?,
+                funds: vec![offer_coin],
+            }
+            .into()
+        }
+        None => native_swap_message(env.contract.address.as_str(), &offer_coin, VESTING_DENOM)?,
+    };
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(SubMsg::reply_on_success(swap_message, SWAP_REPLY_ID))
+        .add_attributes(vec![
+            ("action", "compound"),
+            ("compound_amount", &compound_amount.to_string()),
+        ]))
+}
+
+/// Bond the uluna received from the swap. The market swap has no
+/// slippage bound of its own, so `minimum_receive` is checked here.
+pub(crate) fn bond_swapped(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let staking_info = config.staking_info.unwrap();
+
+    let tmp_swap = TMP_SWAP.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), VESTING_DENOM)?
+        .amount;
+    let swapped = Coin {
+        denom: VESTING_DENOM.to_string(),
+        amount: balance.checked_sub(tmp_swap.uluna_balance)?,
+    };
+    if swapped.amount.is_zero() {
+        return Err(ContractError::NothingToBond {});
+    }
+    if let Some(minimum_receive) = tmp_swap.minimum_receive {
+        if swapped.amount < minimum_receive {
+            return Err(ContractError::MinimumReceiveNotMet {
+                amount: swapped.amount,
+                minimum_receive,
+            });
+        }
+    }
+    TMP_SWAP.remove(deps.storage);
+
+    let bluna_balance = bluna_balance(deps.as_ref(), &env, &staking_info.bluna_token)?;
+    TMP_COMPOUND.save(deps.storage, &bluna_balance)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: staking_info.hub_contract,
+                msg: to_binary(&HubContractExecuteMsg::Bond {})?,
+                funds: vec![swapped.clone()],
+            },
+            BOND_REPLY_ID,
+        ))
+        .add_attribute("bond_amount", swapped.amount.to_string()))
+}
+
+/// Add the bonded bLUNA to the vesting position
+pub(crate) fn add_compounded(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let staking_info = config.staking_info.clone().unwrap();
+    let follow_schedule = config.compounding.as_ref().unwrap().follow_schedule;
+
+    let previous_balance = TMP_COMPOUND.load(deps.storage)?;
+    TMP_COMPOUND.remove(deps.storage);
+    let compounded_amount = bluna_balance(deps.as_ref(), &env, &staking_info.bluna_token)?
+        .checked_sub(previous_balance)?;
+
+    // accrue rewards of the current position before it grows
+    settle_owner_rewards(deps.branch(), &env, &config)?;
+
+    let exchange_rate = query_exchange_rate(deps.as_ref(), &staking_info.hub_contract)?;
+    let mut vesting_info = VESTING_INFO.load(deps.storage)?;
+    vesting_info.add_bond_exchange_rate(exchange_rate, compounded_amount);
+    if follow_schedule {
+        vesting_info.vesting_amount += compounded_amount;
+    } else {
+        vesting_info.compounded_amount += compounded_amount;
+    }
+    VESTING_INFO.save(deps.storage, &vesting_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("compounded_amount", &compounded_amount.to_string()),
+        ("follow_schedule", &follow_schedule.to_string()),
+    ]))
+}
+
+fn bluna_balance(deps: Deps, env: &Env, bluna_token: &str) -> StdResult<Uint128> {
+    let response: BalanceResponse = deps.querier.query_wasm_smart(
+        Addr::unchecked(bluna_token),
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+
+    Ok(response.balance)
+}
+
+/// Terra market module `MsgSwap`, encoded by the field numbers of
+/// terra/market/v1beta1/tx.proto since no generated types are vendored
+fn native_swap_message(trader: &str, offer_coin: &Coin, ask_denom: &str) -> StdResult<CosmosMsg> {
+    let encode_err = |e: protobuf::ProtobufError| StdError::generic_err(e.to_string());
+
+    let mut coin = vec![];
+    {
+        let mut os = CodedOutputStream::vec(&mut coin);
+        os.write_string(1, &offer_coin.denom).map_err(encode_err)?;
+        os.write_string(2, &offer_coin.amount.to_string())
+            .map_err(encode_err)?;
+        os.flush().map_err(encode_err)?;
+    }
+
+    let mut value = vec![];
+    {
+        let mut os = CodedOutputStream::vec(&mut value);
+        os.write_string(1, trader).map_err(encode_err)?;
+        os.write_bytes(2, &coin).map_err(encode_err)?;
+        os.write_string(3, ask_denom).map_err(encode_err)?;
+        os.flush().map_err(encode_err)?;
+    }
+
+    Ok(CosmosMsg::Stargate {
+        type_url: "/terra.market.v1beta1.MsgSwap".to_string(),
+        value: Binary(value),
+    })
+}
@@ -1,53 +1,84 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    to_binary, Addr, Attribute, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    Event, MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
 
-use serde_json::to_string;
-
-use common::vesting::{ExecuteMsg, InstantiateMsg, QueryMsg, VestingInfoResponse};
-use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+use common::error::ContractError;
+use common::events;
+use common::staking::{query_exchange_rate, HubContractExecuteMsg, RewardContractExecuteMsg};
+use common::tax::{deduct_tax, TaxQuerier};
+use common::vesting::{
+    claim_amount, fixed_claim_amount, split_claim, transfer_message, ClaimKind, ClaimPreferences,
+    ClaimRecipient, ConfigResponse, DistributionConfig, ExecuteMsg, InstantiateMsg,
+    LunaValueResponse, NextUnlockResponse, OperatorInfo, OperatorPermissions, OperatorsResponse,
+    QueryMsg, RewardsStrategy, UnlockResponse, UnlockScheduleResponse, VestedAmountResponse,
+    VestingInfoAtResponse, VestingInfoResponse,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom, Expiration};
+use cw_storage_plus::Bound;
 
-use crate::external::handle::{
-    AccruedRewardsResponse, HubContractExecuteMsg, RewardContractExecuteMsg, RewardContractQueryMsg,
+use crate::compound;
+use crate::history::{self, record_claim, record_rewards_claim};
+use crate::rewards::{self, claimable_rewards, settle_owner_rewards, take_rewards};
+use crate::shared::{self, is_shared};
+use crate::solvency::{self, payout_amount};
+use crate::state::{
+    Config, Operator, VestingInfo, CONFIG, LAST_DISTRIBUTION, OPERATORS, REWARD_HOLDERS,
+    REWARD_STATE, VESTING_INFO,
 };
-use crate::state::{Config, VestingInfo, CONFIG, VESTING_INFO};
+use crate::suspension;
 
-const VESTING_DENOM: &str = "uluna";
-const REWARDS_DENOM: &str = "uusd";
+pub(crate) const VESTING_DENOM: &str = "uluna";
+pub(crate) const REWARDS_DENOM: &str = "uusd";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // validate owner address
     deps.api.addr_validate(&msg.owner_address)?;
+    let funder = match msg.funder {
+        Some(funder) => deps.api.addr_validate(&funder)?,
+        None => info.sender.clone(),
+    };
+    let admin = match msg.admin {
+        Some(admin) => Some(deps.api.addr_validate(&admin)?.to_string()),
+        None => None,
+    };
 
     // deposit validation
     if info.funds.len() != 1 || info.funds[0].denom != VESTING_DENOM {
-        return Err(StdError::generic_err(format!(
-            "only {} is allowed to be deposited",
-            VESTING_DENOM
-        )));
+        return Err(ContractError::InvalidDeposit {
+            denom: VESTING_DENOM.to_string(),
+        });
     }
 
+    // a deferred schedule is relative to the start set by the trigger
+    let start_trigger = match msg.start_trigger {
+        Some(start_trigger) => Some(deps.api.addr_validate(&start_trigger)?.to_string()),
+        None => None,
+    };
+    let schedule_time = match start_trigger {
+        Some(_) => 0,
+        None => env.block.time.seconds(),
+    };
+
     // validate vesting schedule with vesting amount
     let vesting_token = info.funds[0].clone();
     msg.vesting_schedule
-        .validate(env.block.time.seconds(), vesting_token.amount)?;
+        .validate(schedule_time, vesting_token.amount)?;
 
     let mut messages: Vec<SubMsg> = vec![];
     let mut attrs: Vec<Attribute> = vec![];
+    let mut events: Vec<Event> = vec![];
     if msg.enable_staking {
         if msg.staking_info.is_none() {
-            return Err(StdError::generic_err(
-                "must provide staking_info to enable staking",
-            ));
+            return Err(ContractError::MissingStakingInfo {});
         }
 
         let staking_info = msg.staking_info.clone().unwrap();
@@ -71,6 +102,10 @@ pub fn instantiate(
                 vesting_amount: Uint128::zero(), // this will be filled at reply
                 vesting_schedule: msg.vesting_schedule,
                 claimed_amount: Uint128::zero(),
+                compounded_amount: Uint128::zero(),
+                bond_exchange_rate: None,
+                start_trigger: start_trigger.clone(),
+                suspended_periods: vec![],
             },
         )?;
     } else {
@@ -78,15 +113,17 @@ pub fn instantiate(
             vec![
                 ("action", "create_vesting_account"),
                 ("owner_address", &msg.owner_address),
-                (
-                    "vesting_denom",
-                    &to_string(&Denom::Native(VESTING_DENOM.to_string())).unwrap(),
-                ),
                 ("vesting_amount", &vesting_token.amount.to_string()),
             ]
             .into_iter()
             .map(|v| v.into()),
         );
+        events.push(events::vesting_created(
+            env.contract.address.as_str(),
+            &msg.owner_address,
+            &Denom::Native(VESTING_DENOM.to_string()),
+            vesting_token.amount,
+        ));
 
         VESTING_INFO.save(
             deps.storage,
@@ -95,10 +132,18 @@ pub fn instantiate(
                 vesting_amount: vesting_token.amount,
                 vesting_schedule: msg.vesting_schedule,
                 claimed_amount: Uint128::zero(),
+                compounded_amount: Uint128::zero(),
+                bond_exchange_rate: None,
+                start_trigger: start_trigger.clone(),
+                suspended_periods: vec![],
             },
         )?;
     }
 
+    if let Some(beneficiaries) = msg.beneficiaries {
+        shared::instantiate_beneficiaries(deps.branch(), beneficiaries)?;
+    }
+
     // store config
     CONFIG.save(
         deps.storage,
@@ -106,22 +151,34 @@ pub fn instantiate(
             owner_address: msg.owner_address,
             staking_enabled: msg.enable_staking,
             staking_info: msg.staking_info,
+            claim_preferences: ClaimPreferences::default(),
+            distribution: DistributionConfig::default(),
+            compounding: None,
+            rewards_strategy: RewardsStrategy::default(),
+            funder: funder.to_string(),
+            admin,
         },
     )?;
 
     Ok(Response::new()
         .add_attributes(attrs)
+        .add_events(events)
         .add_submessages(messages))
 }
 
-/// This will check converted bluna amount and set
-/// the amount as vesting amount.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
-    if msg.id != 1 {
-        return Err(StdError::generic_err("unauthorized"));
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        1 => bonded(deps, env),
+        compound::SWAP_REPLY_ID => compound::bond_swapped(deps, env),
+        compound::BOND_REPLY_ID => compound::add_compounded(deps, env),
+        id => Err(ContractError::UnknownReplyId { id }),
     }
+}
 
+/// This will check converted bluna amount and set
+/// the amount as vesting amount.
+fn bonded(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let staking_info = config.staking_info.unwrap();
     let bluna_token = staking_info.bluna_token;
@@ -131,91 +188,625 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
             address: env.contract.address.to_string(),
         },
     )?;
+    let exchange_rate = query_exchange_rate(deps.as_ref(), &staking_info.hub_contract)?;
 
     VESTING_INFO.update(deps.storage, |mut v| -> StdResult<_> {
         v.vesting_amount = response.balance;
+        v.bond_exchange_rate = Some(exchange_rate);
         Ok(v)
     })?;
 
-    Ok(Response::new().add_attributes(vec![
-        ("action", "create_vesting_account"),
-        ("owner_address", &config.owner_address),
-        (
-            "vesting_denom",
-            &to_string(&Denom::Cw20(Addr::unchecked(bluna_token))).unwrap(),
-        ),
-        ("vesting_amount", &response.balance.to_string()),
-    ]))
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_vesting_account"),
+            ("owner_address", &config.owner_address),
+            ("vesting_amount", &response.balance.to_string()),
+        ])
+        .add_event(events::vesting_created(
+            env.contract.address.as_str(),
+            &config.owner_address,
+            &Denom::Cw20(Addr::unchecked(bluna_token)),
+            response.balance,
+        )))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let shared = is_shared(deps.storage)?;
     match msg {
-        ExecuteMsg::ChangeOwner { new_owner } => change_owner(deps, info, new_owner),
-        ExecuteMsg::Claim { recipient } => claim(deps, env, info, recipient),
-        ExecuteMsg::ClaimRewards { recipient } => claim_rewards(deps, env, info, recipient),
+        ExecuteMsg::ChangeOwner {
+            new_owner,
+            settle_previous_owner,
+        } => change_owner(
+            deps,
+            env,
+            info,
+            new_owner,
+            settle_previous_owner.unwrap_or(true),
+        ),
+        ExecuteMsg::Claim {
+            recipient,
+            amount,
+            recipients: None,
+        } if shared => shared::claim(deps, env, info, recipient, amount),
+        ExecuteMsg::ClaimRewards {
+            recipient,
+            recipients: None,
+        } if shared => shared::claim_rewards(deps, env, info, recipient),
+        ExecuteMsg::Claim { .. }
+        | ExecuteMsg::ClaimAndSend { .. }
+        | ExecuteMsg::ClaimRewards { .. }
+        | ExecuteMsg::Distribute {}
+        | ExecuteMsg::SetCompounding { .. }
+        | ExecuteMsg::Compound { .. }
+        | ExecuteMsg::SetRewardsStrategy { .. }
+        | ExecuteMsg::WithdrawRewardsPosition { .. }
+            if shared =>
+        {
+            Err(ContractError::SharedNotSupported {})
+        }
+        ExecuteMsg::Claim {
+            recipient,
+            amount,
+            recipients,
+        } => claim(deps, env, info, recipient, amount, recipients),
+        ExecuteMsg::ClaimAndSend {
+            contract,
+            msg,
+            amount,
+        } => claim_and_send(deps, env, info, contract, msg, amount),
+        ExecuteMsg::ClaimRewards {
+            recipient,
+            recipients,
+        } => claim_rewards(deps, env, info, recipient, recipients),
+        ExecuteMsg::SetClaimPreferences {
+            principal_recipient,
+            rewards_recipient,
+            allowed_recipients,
+        } => set_claim_preferences(
+            deps,
+            info,
+            ClaimPreferences {
+                principal_recipient,
+                rewards_recipient,
+                allowed_recipients,
+            },
+        ),
+        ExecuteMsg::Distribute {} => distribute(deps, env, info),
+        ExecuteMsg::AddOperator {
+            operator,
+            permissions,
+            expires,
+        } => add_operator(deps, env, info, operator, permissions, expires),
+        ExecuteMsg::RemoveOperator { operator } => remove_operator(deps, info, operator),
+        ExecuteMsg::UpdateBeneficiaries { beneficiaries } => {
+            shared::update_beneficiaries(deps, env, info, beneficiaries)
+        }
+        ExecuteMsg::SetDistribution {
+            distribute_rewards,
+            keeper_bounty,
+        } => set_distribution(
+            deps,
+            info,
+            DistributionConfig {
+                distribute_rewards,
+                keeper_bounty,
+            },
+        ),
+        ExecuteMsg::SetCompounding { compounding } => {
+            compound::set_compounding(deps, info, compounding)
+        }
+        ExecuteMsg::Compound { minimum_receive } => {
+            compound::compound(deps, env, info, minimum_receive)
+        }
+        ExecuteMsg::SetRewardsStrategy { rewards_strategy } => {
+            rewards::set_rewards_strategy(deps, env, info, rewards_strategy)
+        }
+        ExecuteMsg::WithdrawRewardsPosition { recipient } => {
+            rewards::withdraw_rewards_position(deps, env, info, recipient)
+        }
+        ExecuteMsg::Reconcile {} => solvency::reconcile(deps, env, info),
+        ExecuteMsg::Sweep { asset, recipient } => {
+            solvency::sweep(deps, env, info, asset, recipient)
+        }
+        ExecuteMsg::StartVesting {} => start_vesting(deps, env, info),
+        ExecuteMsg::Suspend {} => suspension::suspend(deps, env, info),
+        ExecuteMsg::Resume {} => suspension::resume(deps, env, info),
+    }
+}
+
+/// Start a deferred vesting: the relative schedule is moved
+/// to begin at the current block time
+fn start_vesting(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    match &vesting_info.start_trigger {
+        None => return Err(ContractError::VestingAlreadyStarted {}),
+        Some(start_trigger) if *start_trigger != info.sender => {
+            return Err(ContractError::Unauthorized {})
+        }
+        Some(_) => {}
     }
+
+    vesting_info.vesting_schedule = vesting_info
+        .vesting_schedule
+        .shifted(env.block.time.seconds())?;
+    vesting_info.start_trigger = None;
+    VESTING_INFO.save(deps.storage, &vesting_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "start_vesting"),
+        ("start_time", &vesting_info.vesting_schedule.start_time),
+        ("end_time", &vesting_info.vesting_schedule.end_time),
+    ]))
 }
 
-fn change_owner(deps: DepsMut, info: MessageInfo, new_owner: String) -> StdResult<Response> {
+/// Transfer the ownership to `new_owner`. When `settle_previous_owner`
+/// is set, the outgoing owner is paid everything vested and accrued up to
+/// this block, so only the future stream moves to the new owner.
+fn change_owner(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    settle_previous_owner: bool,
+) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
     if config.owner_address != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+
+    deps.api.addr_validate(&new_owner)?;
+    let previous_owner = config.owner_address.to_string();
+
+    // the rewards position belongs to the current owner
+    if config.staking_enabled && !rewards::rewards_position(deps.as_ref(), &env, &config)?.is_zero()
+    {
+        return Err(ContractError::RewardsPositionOpen {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attrs: Vec<Attribute> = vec![];
+    let mut claim_indexes: Vec<Attribute> = vec![];
+    let mut events: Vec<Event> = vec![];
+
+    // shared vesting contracts only change the admin of the beneficiaries
+    if !is_shared(deps.storage)? {
+        let mut settled_amount = Uint128::zero();
+        let mut settled_rewards = Uint128::zero();
+
+        let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+        let vested_amount = vesting_info.vested_amount(env.block.time.seconds())?;
+        if settle_previous_owner && vested_amount > vesting_info.claimed_amount {
+            let (vesting_info, _, claim_amount) =
+                update_claimed_amount(deps.branch(), env.clone(), None)?;
+            messages.push(transfer_message(
+                &deps.querier,
+                &vesting_info.vesting_denom,
+                previous_owner.to_string(),
+                claim_amount,
+            )?);
+            let (claim_index, event) = record_claim(
+                deps.storage,
+                &env,
+                ClaimKind::Principal,
+                &previous_owner,
+                &previous_owner,
+                vesting_info.vesting_denom,
+                claim_amount,
+            )?;
+            claim_indexes.push(claim_index);
+            events.push(event);
+            settled_amount = claim_amount;
+        }
+
+        // rewards accrued so far are settled to the previous owner,
+        // and the new owner starts accruing from the current index
+        let mut pending_rewards = Uint128::zero();
+        if config.staking_enabled {
+            // when the reward contract is unavailable, only rewards settled
+            // before stay with the previous owner and nothing is paid out
+            let settled = rewards::try_settle_owner_rewards(deps.branch(), &env, &config)?;
+            pending_rewards = match settled {
+                Some(pending_rewards) => pending_rewards,
+                None => {
+                    REWARD_HOLDERS
+                        .may_load(deps.storage, previous_owner.to_string())?
+                        .unwrap_or_default()
+                        .pending_rewards
+                }
+            };
+            if settle_previous_owner && settled.is_some() && !pending_rewards.is_zero() {
+                messages.extend(take_rewards(
+                    deps.branch(),
+                    &env,
+                    &config,
+                    &previous_owner,
+                    pending_rewards,
+                )?);
+                messages.push(rewards_transfer_message(
+                    &deps.querier,
+                    previous_owner.to_string(),
+                    pending_rewards,
+                )?);
+                let (claim_index, event) = record_rewards_claim(
+                    deps.storage,
+                    &env,
+                    &previous_owner,
+                    &previous_owner,
+                    pending_rewards,
+                )?;
+                claim_indexes.push(claim_index);
+                events.push(event);
+                settled_rewards = pending_rewards;
+                pending_rewards = Uint128::zero();
+            }
+
+            if pending_rewards.is_zero() {
+                REWARD_HOLDERS.remove(deps.storage, previous_owner.to_string());
+            }
+
+            let reward_state = REWARD_STATE.may_load(deps.storage)?.unwrap_or_default();
+            let mut holder = REWARD_HOLDERS
+                .may_load(deps.storage, new_owner.to_string())?
+                .unwrap_or_default();
+            holder.reward_index = reward_state.reward_index;
+            REWARD_HOLDERS.save(deps.storage, new_owner.to_string(), &holder)?;
+        }
+
+        let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+        attrs.extend(
+            vec![
+                ("settle_previous_owner", settle_previous_owner.to_string()),
+                ("vested_amount", vested_amount.to_string()),
+                ("claimed_amount", vesting_info.claimed_amount.to_string()),
+                ("settled_amount", settled_amount.to_string()),
+                ("settled_rewards", settled_rewards.to_string()),
+                ("pending_rewards", pending_rewards.to_string()),
+                (
+                    "remaining_amount",
+                    vesting_info
+                        .total_amount()
+                        .checked_sub(vesting_info.claimed_amount)?
+                        .to_string(),
+                ),
+            ]
+            .into_iter()
+            .map(|v| v.into()),
+        );
     }
 
+    // routing preferences, reward settings and operators belong to the previous owner
     config.owner_address = new_owner.to_string();
+    config.claim_preferences = ClaimPreferences::default();
+    config.distribution = DistributionConfig::default();
+    config.compounding = None;
+    config.rewards_strategy = RewardsStrategy::default();
     CONFIG.save(deps.storage, &config)?;
 
+    let operators: Vec<Vec<u8>> = OPERATORS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect();
+    for operator in operators {
+        OPERATORS.remove(
+            deps.storage,
+            String::from_utf8(operator).map_err(StdError::from)?,
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "change_owner"),
+            ("previous_owner", previous_owner.as_str()),
+            ("new_owner", new_owner.as_str()),
+        ])
+        .add_attributes(attrs)
+        .add_attributes(claim_indexes)
+        .add_events(events)
+        .add_event(events::owner_changed(
+            env.contract.address.as_str(),
+            &new_owner,
+            &previous_owner,
+        )))
+}
+
+/// Check `sender` is the owner or an operator having the permission
+/// selected by `permitted`. Returns true when `sender` is an operator.
+pub(crate) fn check_permission(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    sender: &Addr,
+    permitted: fn(&OperatorPermissions) -> bool,
+) -> Result<bool, ContractError> {
+    if config.owner_address == *sender {
+        return Ok(false);
+    }
+
+    match OPERATORS.may_load(deps.storage, sender.to_string())? {
+        Some(operator)
+            if permitted(&operator.permissions) && !operator.expires.is_expired(&env.block) =>
+        {
+            Ok(true)
+        }
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub(crate) fn check_recipient(
+    config: &Config,
+    is_operator: bool,
+    recipient: &str,
+) -> Result<(), ContractError> {
+    if is_operator {
+        config
+            .claim_preferences
+            .check_operator_recipient(&config.owner_address, recipient)
+    } else {
+        config
+            .claim_preferences
+            .check_recipient(&config.owner_address, recipient)
+    }
+}
+
+fn add_operator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    permissions: OperatorPermissions,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    deps.api.addr_validate(&operator)?;
+    if operator == config.owner_address {
+        return Err(ContractError::OwnerAsOperator {});
+    }
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::ExpirationPassed {});
+    }
+
+    OPERATORS.save(
+        deps.storage,
+        operator.to_string(),
+        &Operator {
+            permissions,
+            expires,
+        },
+    )?;
+
     Ok(Response::new().add_attributes(vec![
-        ("action", "change_owner"),
-        ("new_owner", new_owner.as_str()),
+        ("action", "add_operator"),
+        ("operator", operator.as_str()),
     ]))
 }
 
-fn claim(
+fn remove_operator(
     deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !OPERATORS.has(deps.storage, operator.to_string()) {
+        return Err(ContractError::OperatorNotFound {});
+    }
+
+    OPERATORS.remove(deps.storage, operator.to_string());
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_operator"),
+        ("operator", operator.as_str()),
+    ]))
+}
+
+fn set_claim_preferences(
+    deps: DepsMut,
+    info: MessageInfo,
+    claim_preferences: ClaimPreferences,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(allowed_recipients) = &claim_preferences.allowed_recipients {
+        for recipient in allowed_recipients.iter() {
+            deps.api.addr_validate(recipient)?;
+        }
+    }
+
+    // default recipients must pass the allowlist as well
+    for recipient in claim_preferences
+        .principal_recipient
+        .iter()
+        .chain(claim_preferences.rewards_recipient.iter())
+    {
+        deps.api.addr_validate(recipient)?;
+        claim_preferences.check_recipient(&config.owner_address, recipient)?;
+    }
+
+    config.claim_preferences = claim_preferences;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "set_claim_preferences"))
+}
+
+fn claim(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: Option<String>,
-) -> StdResult<Response> {
-    let sender = info.sender;
-    let recipient = recipient.unwrap_or_else(|| sender.to_string());
-
+    amount: Option<Uint128>,
+    recipients: Option<Vec<ClaimRecipient>>,
+) -> Result<Response, ContractError> {
     // permission check
     let config: Config = CONFIG.load(deps.storage)?;
-    if config.owner_address != sender {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    let is_operator = check_permission(deps.as_ref(), &env, &config, &info.sender, |p| p.claim)?;
 
-    let mut vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
-    let vested_amount = vesting_info
-        .vesting_schedule
-        .vested_amount(env.block.time.seconds(), vesting_info.vesting_amount)?;
-    let claimed_amount = vesting_info.claimed_amount;
+    if let Some(recipients) = recipients {
+        if recipient.is_some() {
+            return Err(ContractError::ConflictingRecipients {});
+        }
+
+        for r in recipients.iter() {
+            deps.api.addr_validate(&r.address)?;
+            check_recipient(&config, is_operator, &r.address)?;
+        }
 
-    let claimable_amount = vested_amount.checked_sub(claimed_amount)?;
-    if claimable_amount.is_zero() {
-        return Err(StdError::generic_err("nothing to claim"));
+        let amount = amount.or_else(|| fixed_claim_amount(&recipients));
+        let previous_claimed_amount = VESTING_INFO.load(deps.storage)?.claimed_amount;
+        let (vesting_info, vested_amount, claim_amount) =
+            update_claimed_amount(deps.branch(), env.clone(), amount)?;
+
+        // shares are split from what the schedule advanced by,
+        // then scaled down to what is paid out in a deficit
+        let scheduled_amount = vesting_info
+            .claimed_amount
+            .checked_sub(previous_claimed_amount)?;
+        let shares = solvency::scaled_shares(
+            split_claim(scheduled_amount, &recipients)?,
+            scheduled_amount,
+            claim_amount,
+        )?;
+
+        let mut messages: Vec<CosmosMsg> = vec![];
+        let mut attrs: Vec<Attribute> = vec![];
+        let mut events: Vec<Event> = vec![];
+        for (address, amount) in shares {
+            attrs.push(("recipient", address.as_str()).into());
+            attrs.push(("recipient_amount", amount.to_string()).into());
+            if !amount.is_zero() {
+                let (claim_index, event) = record_claim(
+                    deps.storage,
+                    &env,
+                    ClaimKind::Principal,
+                    &config.owner_address,
+                    &address,
+                    vesting_info.vesting_denom.clone(),
+                    amount,
+                )?;
+                attrs.push(claim_index);
+                events.push(event);
+                messages.push(transfer_message(
+                    &deps.querier,
+                    &vesting_info.vesting_denom,
+                    address,
+                    amount,
+                )?);
+            }
+        }
+
+        return Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "claim")
+            .add_attributes(attrs)
+            .add_attributes(vec![
+                ("vesting_amount", &vesting_info.total_amount().to_string()),
+                ("vested_amount", &vested_amount.to_string()),
+                ("claim_amount", &claim_amount.to_string()),
+            ])
+            .add_events(events));
     }
 
-    vesting_info.claimed_amount = vested_amount;
-    VESTING_INFO.save(deps.storage, &vesting_info)?;
+    let recipient = recipient
+        .or_else(|| config.claim_preferences.principal_recipient.clone())
+        .unwrap_or_else(|| config.owner_address.to_string());
+    check_recipient(&config, is_operator, &recipient)?;
+
+    let (vesting_info, vested_amount, claim_amount) =
+        update_claimed_amount(deps.branch(), env.clone(), amount)?;
 
     // depends on vesting_denom, make native or cw20 transfer message
+    let message = transfer_message(
+        &deps.querier,
+        &vesting_info.vesting_denom,
+        recipient.clone(),
+        claim_amount,
+    )?;
+    let (claim_index, event) = record_claim(
+        deps.storage,
+        &env,
+        ClaimKind::Principal,
+        &config.owner_address,
+        &recipient,
+        vesting_info.vesting_denom.clone(),
+        claim_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![("action", "claim"), ("recipient", recipient.as_str())])
+        .add_attributes(vec![
+            ("vesting_amount", &vesting_info.total_amount().to_string()),
+            ("vested_amount", &vested_amount.to_string()),
+            ("claim_amount", &claim_amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
+
+/// Claim vested tokens and send them into the given contract
+/// with `msg` in a single transaction. cw20 tokens are delivered
+/// through `Cw20ExecuteMsg::Send` and native tokens are attached
+/// as funds to the execution of `msg`.
+fn claim_and_send(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    msg: Binary,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    // permission check
+    let config: Config = CONFIG.load(deps.storage)?;
+    let is_operator = check_permission(deps.as_ref(), &env, &config, &info.sender, |p| p.claim)?;
+
+    deps.api.addr_validate(&contract)?;
+    check_recipient(&config, is_operator, &contract)?;
+
+    let (vesting_info, vested_amount, claim_amount) =
+        update_claimed_amount(deps.branch(), env.clone(), amount)?;
+    let (claim_index, event) = record_claim(
+        deps.storage,
+        &env,
+        ClaimKind::Principal,
+        &config.owner_address,
+        &contract,
+        vesting_info.vesting_denom.clone(),
+        claim_amount,
+    )?;
+
     let message: CosmosMsg = match vesting_info.vesting_denom.clone() {
-        Denom::Native(denom) => BankMsg::Send {
-            to_address: recipient.clone(),
-            amount: vec![Coin {
+        Denom::Native(denom) => WasmMsg::Execute {
+            contract_addr: contract.clone(),
+            msg,
+            funds: vec![Coin {
                 denom,
-                amount: claimable_amount,
+                amount: claim_amount,
             }],
         }
         .into(),
         Denom::Cw20(contract_addr) => WasmMsg::Execute {
             contract_addr: contract_addr.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: recipient.clone(),
-                amount: claimable_amount,
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: contract.clone(),
+                amount: claim_amount,
+                msg,
             })?,
             funds: vec![],
         }
@@ -224,129 +815,572 @@ fn claim(
 
     Ok(Response::new()
         .add_message(message)
-        .add_attributes(vec![("action", "claim"), ("recipient", recipient.as_str())])
         .add_attributes(vec![
-            (
-                "vesting_denom",
-                &to_string(&vesting_info.vesting_denom).unwrap(),
-            ),
-            ("vesting_amount", &vesting_info.vesting_amount.to_string()),
+            ("action", "claim_and_send"),
+            ("contract", contract.as_str()),
+        ])
+        .add_attributes(vec![
+            ("vesting_amount", &vesting_info.total_amount().to_string()),
             ("vested_amount", &vested_amount.to_string()),
-            ("claim_amount", &claimable_amount.to_string()),
-        ]))
+            ("claim_amount", &claim_amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
+
+/// Compute the amount to be claimed and advance `claimed_amount` by it.
+/// When `amount` is not given, the whole claimable amount is claimed.
+fn update_claimed_amount(
+    mut deps: DepsMut,
+    env: Env,
+    amount: Option<Uint128>,
+) -> Result<(VestingInfo, Uint128, Uint128), ContractError> {
+    let mut vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let vested_amount = vesting_info.vested_amount(env.block.time.seconds())?;
+    let claimed_amount = vesting_info.claimed_amount;
+
+    let claim_amount = claim_amount(vested_amount.checked_sub(claimed_amount)?, amount)?;
+    let payout_amount = payout_amount(deps.as_ref(), &env, &vesting_info, claim_amount)?;
+
+    // claimed bLUNA stops earning rewards from now on, unless the reward
+    // contract is unavailable and the rewards are settled later
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.staking_enabled {
+        rewards::try_settle_owner_rewards(deps.branch(), &env, &config)?;
+    }
+
+    // the schedule advances by the full amount even when less is paid out
+    vesting_info.claimed_amount = claimed_amount.checked_add(claim_amount)?;
+    VESTING_INFO.save(deps.storage, &vesting_info)?;
+
+    Ok((vesting_info, vested_amount, payout_amount))
 }
 
 fn claim_rewards(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: Option<String>,
-) -> StdResult<Response> {
-    let sender = info.sender;
-    let recipient = recipient.unwrap_or_else(|| sender.to_string());
-
+    recipients: Option<Vec<ClaimRecipient>>,
+) -> Result<Response, ContractError> {
     // permission check
     let config: Config = CONFIG.load(deps.storage)?;
-    if config.owner_address != sender {
-        return Err(StdError::generic_err("unauthorized"));
-    }
+    let is_operator = match check_permission(deps.as_ref(), &env, &config, &info.sender, |p| {
+        p.claim_rewards
+    }) {
+        Ok(is_operator) => is_operator,
+        // previous owners can claim rewards settled to them
+        Err(_)
+            if recipients.is_none()
+                && REWARD_HOLDERS.has(deps.storage, info.sender.to_string()) =>
+        {
+            return rewards::claim_settled_rewards(deps, env, info, recipient);
+        }
+        Err(err) => return Err(err),
+    };
 
     if !config.staking_enabled {
-        return Err(StdError::generic_err("staking disabled"));
+        return Err(ContractError::StakingDisabled {});
+    }
+
+    if recipient.is_some() && recipients.is_some() {
+        return Err(ContractError::ConflictingRecipients {});
+    }
+
+    // only rewards accrued while the current owner holds the position
+    let rewards_amount = settle_owner_rewards(deps.branch(), &env, &config)?;
+
+    if config.rewards_strategy != RewardsStrategy::Payout {
+        if recipient.is_some() || recipients.is_some() {
+            return Err(ContractError::RecipientNotSupported {});
+        }
+
+        return rewards::store_rewards(deps, env, config, rewards_amount);
     }
 
     let mut messages: Vec<CosmosMsg> = vec![];
     let mut rewards_claim_amount = Uint128::zero();
 
-    // check reward_denom balance
-    let existing_rewards: Coin = deps
-        .querier
-        .query_balance(env.contract.address.to_string(), REWARDS_DENOM)?;
-    if !existing_rewards.amount.is_zero() {
-        rewards_claim_amount += existing_rewards.amount;
-
-        messages.push(
-            BankMsg::Send {
-                to_address: recipient.clone(),
-                amount: vec![existing_rewards],
+    if let Some(recipients) = recipients {
+        for r in recipients.iter() {
+            deps.api.addr_validate(&r.address)?;
+            check_recipient(&config, is_operator, &r.address)?;
+        }
+
+        let rewards_amount = fixed_claim_amount(&recipients).unwrap_or(rewards_amount);
+        messages.extend(take_rewards(
+            deps.branch(),
+            &env,
+            &config,
+            &config.owner_address,
+            rewards_amount,
+        )?);
+
+        let mut attrs: Vec<Attribute> = vec![];
+        let mut events: Vec<Event> = vec![];
+        for (address, amount) in split_claim(rewards_amount, &recipients)? {
+            attrs.push(("recipient", address.as_str()).into());
+            attrs.push(("recipient_amount", amount.to_string()).into());
+            if !amount.is_zero() {
+                rewards_claim_amount += amount;
+                let (claim_index, event) = record_rewards_claim(
+                    deps.storage,
+                    &env,
+                    &config.owner_address,
+                    &address,
+                    amount,
+                )?;
+                attrs.push(claim_index);
+                events.push(event);
+                messages.push(rewards_transfer_message(&deps.querier, address, amount)?);
             }
-            .into(),
-        );
+        }
+
+        return Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "claim_rewards")
+            .add_attributes(attrs)
+            .add_attribute("rewards_claim_amount", rewards_claim_amount.to_string())
+            .add_events(events));
     }
 
-    // check reward_contract rewards
-    let staking_info = config.staking_info.unwrap();
-    let response: AccruedRewardsResponse = deps.querier.query_wasm_smart(
-        Addr::unchecked(staking_info.reward_contract.to_string()),
-        &RewardContractQueryMsg::AccruedRewards {
-            address: env.contract.address.to_string(),
-        },
-    )?;
-    if !response.rewards.is_zero() {
-        rewards_claim_amount += response.rewards;
+    let recipient = recipient
+        .or_else(|| config.claim_preferences.rewards_recipient.clone())
+        .unwrap_or_else(|| config.owner_address.to_string());
+    check_recipient(&config, is_operator, &recipient)?;
 
-        messages.push(
-            WasmMsg::Execute {
-                contract_addr: staking_info.reward_contract,
-                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards {
-                    recipient: Some(recipient),
-                })?,
-                funds: vec![],
-            }
-            .into(),
-        );
+    let mut claim_indexes: Vec<Attribute> = vec![];
+    let mut events: Vec<Event> = vec![];
+    if !rewards_amount.is_zero() {
+        rewards_claim_amount += rewards_amount;
+
+        messages.extend(take_rewards(
+            deps.branch(),
+            &env,
+            &config,
+            &config.owner_address,
+            rewards_amount,
+        )?);
+        let (claim_index, event) = record_rewards_claim(
+            deps.storage,
+            &env,
+            &config.owner_address,
+            &recipient,
+            rewards_amount,
+        )?;
+        claim_indexes.push(claim_index);
+        events.push(event);
+        messages.push(rewards_transfer_message(
+            &deps.querier,
+            recipient,
+            rewards_amount,
+        )?);
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "claim_rewards"),
-        (
-            "rewards_claim_amount",
-            rewards_claim_amount.to_string().as_str(),
-        ),
-    ]))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "claim_rewards"),
+            (
+                "rewards_claim_amount",
+                rewards_claim_amount.to_string().as_str(),
+            ),
+        ])
+        .add_attributes(claim_indexes)
+        .add_events(events))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::VestingInfo {} => to_binary(&vesting_account(deps, env)?),
+/// Withdraw accrued rewards from the reward contract to this contract
+pub(crate) fn withdraw_rewards_message(reward_contract: String) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: reward_contract,
+        msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })?,
+        funds: vec![],
     }
+    .into())
 }
 
-fn vesting_account(deps: Deps, env: Env) -> StdResult<VestingInfoResponse> {
+/// uusd rewards are sent with the stability tax deducted,
+/// so sending `amount` costs exactly `amount`
+pub(crate) fn rewards_transfer_message(
+    querier: &dyn TaxQuerier,
+    recipient: String,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(BankMsg::Send {
+        to_address: recipient,
+        amount: vec![deduct_tax(
+            querier,
+            Coin {
+                denom: REWARDS_DENOM.to_string(),
+                amount,
+            },
+        )?],
+    }
+    .into())
+}
+
+/// Push claimable principal, and rewards when enabled, to the owner's
+/// configured recipients. Anyone can execute this at most once per
+/// vesting interval, and the caller can take a bounty from the rewards.
+fn distribute(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
 
-    let vested_amount = vesting_info
+    let block_time = env.block.time.seconds();
+    let vesting_interval = vesting_info
         .vesting_schedule
-        .vested_amount(env.block.time.seconds(), vesting_info.vesting_amount)?;
+        .vesting_interval
+        .parse::<u64>()
+        .unwrap();
+    if let Some(last_distribution) = LAST_DISTRIBUTION.may_load(deps.storage)? {
+        if block_time < last_distribution + vesting_interval {
+            return Err(ContractError::DistributionTooEarly {});
+        }
+    }
+
+    LAST_DISTRIBUTION.save(deps.storage, &block_time)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attrs: Vec<Attribute> = vec![
+        ("action", "distribute").into(),
+        ("keeper", info.sender.as_str()).into(),
+    ];
+    let mut events: Vec<Event> = vec![];
+
+    // principal
+    let vested_amount = vesting_info.vested_amount(block_time)?;
     let claimable_amount = vested_amount.checked_sub(vesting_info.claimed_amount)?;
+    if !claimable_amount.is_zero() {
+        let recipient = config
+            .claim_preferences
+            .principal_recipient
+            .clone()
+            .unwrap_or_else(|| config.owner_address.to_string());
+
+        let (vesting_info, _, claim_amount) =
+            update_claimed_amount(deps.branch(), env.clone(), None)?;
+        messages.push(transfer_message(
+            &deps.querier,
+            &vesting_info.vesting_denom,
+            recipient.to_string(),
+            claim_amount,
+        )?);
+
+        attrs.push(("recipient", recipient.as_str()).into());
+        attrs.push(("claim_amount", claim_amount.to_string()).into());
+        let (claim_index, event) = record_claim(
+            deps.storage,
+            &env,
+            ClaimKind::Principal,
+            &config.owner_address,
+            &recipient,
+            vesting_info.vesting_denom,
+            claim_amount,
+        )?;
+        attrs.push(claim_index);
+        events.push(event);
+    }
+
+    // rewards, unless they are kept as the rewards position
+    if config.staking_enabled
+        && config.distribution.distribute_rewards
+        && config.rewards_strategy == RewardsStrategy::Payout
+    {
+        // the principal is still distributed when the reward contract is unavailable
+        let rewards_amount =
+            rewards::try_settle_owner_rewards(deps.branch(), &env, &config)?.unwrap_or_default();
+        if !rewards_amount.is_zero() {
+            messages.extend(take_rewards(
+                deps.branch(),
+                &env,
+                &config,
+                &config.owner_address,
+                rewards_amount,
+            )?);
+        }
+
+        let keeper_bounty = config.distribution.keeper_bounty * rewards_amount;
+        let rewards_claim_amount = rewards_amount.checked_sub(keeper_bounty)?;
+        if !rewards_claim_amount.is_zero() {
+            let rewards_recipient = config
+                .claim_preferences
+                .rewards_recipient
+                .clone()
+                .unwrap_or_else(|| config.owner_address.to_string());
+
+            messages.push(rewards_transfer_message(
+                &deps.querier,
+                rewards_recipient.to_string(),
+                rewards_claim_amount,
+            )?);
+
+            attrs.push(("rewards_recipient", rewards_recipient.as_str()).into());
+            attrs.push(("rewards_claim_amount", rewards_claim_amount.to_string()).into());
+            let (claim_index, event) = record_rewards_claim(
+                deps.storage,
+                &env,
+                &config.owner_address,
+                &rewards_recipient,
+                rewards_claim_amount,
+            )?;
+            attrs.push(claim_index);
+            events.push(event);
+        }
+
+        if !keeper_bounty.is_zero() {
+            messages.push(rewards_transfer_message(
+                &deps.querier,
+                info.sender.to_string(),
+                keeper_bounty,
+            )?);
+
+            attrs.push(("keeper_bounty", keeper_bounty.to_string()).into());
+            let (claim_index, event) = record_rewards_claim(
+                deps.storage,
+                &env,
+                &config.owner_address,
+                info.sender.as_str(),
+                keeper_bounty,
+            )?;
+            attrs.push(claim_index);
+            events.push(event);
+        }
+    }
+
+    if messages.is_empty() {
+        return Err(ContractError::NothingToDistribute {});
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attrs)
+        .add_events(events))
+}
+
+fn set_distribution(
+    deps: DepsMut,
+    info: MessageInfo,
+    distribution: DistributionConfig,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if distribution.keeper_bounty > Decimal::percent(MAX_KEEPER_BOUNTY) {
+        return Err(ContractError::InvalidKeeperBounty {});
+    }
+
+    config.distribution = distribution;
+    CONFIG.save(deps.storage, &config)?;
 
+    Ok(Response::new().add_attribute("action", "set_distribution"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    Ok(match msg {
+        QueryMsg::VestingInfo {} => to_binary(&vesting_account(deps, env)?),
+        QueryMsg::Operators { start_after, limit } => {
+            to_binary(&operators(deps, start_after, limit)?)
+        }
+        QueryMsg::Beneficiary { address } => {
+            to_binary(&shared::query_beneficiary(deps, env, address)?)
+        }
+        QueryMsg::Beneficiaries { start_after, limit } => {
+            to_binary(&shared::query_beneficiaries(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Solvency {} => to_binary(&solvency::query_solvency(deps, env)?),
+        QueryMsg::VestingInfoAt { time } => to_binary(&vesting_account_at(deps, env, time)?),
+        QueryMsg::VestedAmount { time } => to_binary(&vested_amount_at(deps, time)?),
+        QueryMsg::UnlockSchedule { start_after, limit } => {
+            to_binary(&unlock_schedule(deps, env, start_after, limit)?)
+        }
+        QueryMsg::NextUnlock {} => to_binary(&next_unlock(deps, env)?),
+        QueryMsg::ClaimHistory { start_after, limit } => {
+            to_binary(&history::query_claim_history(deps, start_after, limit)?)
+        }
+        QueryMsg::Rewards {} => to_binary(&rewards::query_rewards_info(deps, env)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Suspensions {} => to_binary(&suspension::query_suspensions(deps, env)?),
+    }?)
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_KEEPER_BOUNTY: u64 = 10;
+
+fn operators(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OperatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let operators = OPERATORS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (operator, v) = item?;
+            Ok(OperatorInfo {
+                operator: String::from_utf8(operator)?,
+                permissions: v.permissions,
+                expires: v.expires,
+            })
+        })
+        .collect::<StdResult<Vec<OperatorInfo>>>()?;
+
+    Ok(OperatorsResponse { operators })
+}
+
+fn vesting_account(deps: Deps, env: Env) -> StdResult<VestingInfoResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+
+    let vested_amount = vesting_info.vested_amount(env.block.time.seconds())?;
+    // what Claim pays, scaled to the holdings in a deficit
+    let claimable_amount = payout_amount(
+        deps,
+        &env,
+        &vesting_info,
+        vested_amount.checked_sub(vesting_info.claimed_amount)?,
+    )?;
+    let vesting_schedule = vesting_info.effective_schedule(env.block.time.seconds())?;
+
+    // staking contract errors must not hide the principal
     let mut claimable_staking_rewards: Uint128 = Uint128::zero();
+    let mut luna_value: Option<LunaValueResponse> = None;
+    let mut rewards_error: Option<String> = None;
     if config.staking_enabled {
-        let staking_info = config.staking_info.unwrap();
+        let staking_status = || -> StdResult<(Uint128, LunaValueResponse)> {
+            let staking_info = config.staking_info.clone().unwrap();
+            let exchange_rate = query_exchange_rate(deps, &staking_info.hub_contract)?;
+            let luna_value = LunaValueResponse::new(
+                exchange_rate,
+                vesting_info.bond_exchange_rate.unwrap_or(exchange_rate),
+                vesting_info.total_amount(),
+                vested_amount,
+                claimable_amount,
+            );
 
-        let existing_rewards: Coin = deps
-            .querier
-            .query_balance(env.contract.address.to_string(), REWARDS_DENOM)?;
-        claimable_staking_rewards += existing_rewards.amount;
+            Ok((claimable_rewards(deps, &env, &config)?, luna_value))
+        };
 
-        let response: AccruedRewardsResponse = deps.querier.query_wasm_smart(
-            Addr::unchecked(staking_info.reward_contract),
-            &RewardContractQueryMsg::AccruedRewards {
-                address: env.contract.address.to_string(),
-            },
-        )?;
-        claimable_staking_rewards += response.rewards;
+        match staking_status() {
+            Ok((rewards, value)) => {
+                claimable_staking_rewards = rewards;
+                luna_value = Some(value);
+            }
+            Err(err) => rewards_error = Some(err.to_string()),
+        }
     }
 
     Ok(VestingInfoResponse {
         owner_address: config.owner_address,
+        vesting_amount: vesting_info.total_amount(),
         vesting_denom: vesting_info.vesting_denom,
-        vesting_amount: vesting_info.vesting_amount,
         vested_amount,
-        vesting_schedule: vesting_info.vesting_schedule,
+        vesting_schedule,
         claimable_amount,
         claimable_staking_rewards,
+        luna_value,
+        rewards_error,
     })
 }
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        owner_address: config.owner_address,
+        funder: config.funder,
+        staking_enabled: config.staking_enabled,
+        staking_info: config.staking_info,
+        claim_preferences: config.claim_preferences,
+        distribution: config.distribution,
+        compounding: config.compounding,
+        rewards_strategy: config.rewards_strategy,
+        start_trigger: vesting_info.start_trigger,
+        admin: config.admin,
+    })
+}
+
+/// Only reads the schedule and the principal balance,
+/// so it never fails on staking contract errors
+fn vesting_account_at(deps: Deps, env: Env, time: u64) -> StdResult<VestingInfoAtResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+
+    let vested_amount = vesting_info.vested_amount(time)?;
+    let vesting_schedule = vesting_info.effective_schedule(time)?;
+    // what Claim would pay with the current holdings
+    let claimable_amount = payout_amount(
+        deps,
+        &env,
+        &vesting_info,
+        vested_amount.saturating_sub(vesting_info.claimed_amount),
+    )?;
+
+    Ok(VestingInfoAtResponse {
+        time,
+        owner_address: config.owner_address,
+        vesting_amount: vesting_info.total_amount(),
+        vesting_denom: vesting_info.vesting_denom,
+        vested_amount,
+        claimed_amount: vesting_info.claimed_amount,
+        claimable_amount,
+        vesting_schedule,
+    })
+}
+
+fn vested_amount_at(deps: Deps, time: u64) -> StdResult<VestedAmountResponse> {
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+
+    Ok(VestedAmountResponse {
+        time,
+        vested_amount: vesting_info.vested_amount(time)?,
+    })
+}
+
+fn unlock_schedule(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UnlockScheduleResponse> {
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let deficit = solvency::deficit(deps, &env, &vesting_info)?;
+    let unlocks = vesting_info
+        .unlocks(start_after)?
+        .take(limit)
+        .map(|unlock| Ok(scaled_unlock(unlock?, deficit)))
+        .collect::<StdResult<Vec<UnlockResponse>>>()?;
+
+    Ok(UnlockScheduleResponse { unlocks })
+}
+
+/// In a deficit, an unlock pays out pro rata to the holdings like claims
+fn scaled_unlock(
+    mut unlock: UnlockResponse,
+    deficit: Option<(Uint128, Uint128)>,
+) -> UnlockResponse {
+    unlock.amount = solvency::scaled_amount(unlock.amount, deficit);
+    unlock
+}
+
+fn next_unlock(deps: Deps, env: Env) -> StdResult<NextUnlockResponse> {
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let deficit = solvency::deficit(deps, &env, &vesting_info)?;
+    let unlock = vesting_info
+        .unlocks(Some(env.block.time.seconds()))?
+        .next()
+        .transpose()?
+        .map(|unlock| scaled_unlock(unlock, deficit));
+
+    Ok(NextUnlockResponse { unlock })
+}
@@ -1,32 +1,35 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum RewardContractExecuteMsg {
-    /// Request bAsset reward withdrawal
-    ClaimRewards { recipient: Option<String> },
+pub enum AssetInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum RewardContractQueryMsg {
-    /// Request bAsset reward amount
-    AccruedRewards { address: String },
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum HubContractExecuteMsg {
-    /// Receives `amount` in underlying coin denom from sender.
-    /// Delegate `amount` equally between validators from the registry.
-    /// Issue `amount` / exchange_rate for the user.
-    Bond {},
+pub enum PairExecuteMsg {
+    /// Swap the native `offer_asset` sent with this message
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct AccruedRewardsResponse {
-    pub rewards: Uint128,
+pub enum MarketExecuteMsg {
+    /// Deposit the sent stable coins and mint aTerra to the sender
+    DepositStable {},
 }
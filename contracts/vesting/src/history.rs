@@ -0,0 +1,86 @@
+use cosmwasm_std::{Attribute, Deps, Env, Event, Order, StdResult, Storage, Uint128};
+use cw20::Denom;
+use cw_storage_plus::{Bound, U64Key};
+
+use common::events;
+use common::vesting::{ClaimHistoryResponse, ClaimKind, ClaimRecord};
+
+use crate::contract::REWARDS_DENOM;
+use crate::state::{CLAIM_COUNT, CLAIM_HISTORY};
+
+/// Append a claim of `owner` to the history. Returns its `claim_index`
+/// attribute and the `vesting_claimed` or `rewards_claimed` event.
+pub(crate) fn record_claim(
+    storage: &mut dyn Storage,
+    env: &Env,
+    kind: ClaimKind,
+    owner: &str,
+    recipient: &str,
+    denom: Denom,
+    amount: Uint128,
+) -> StdResult<(Attribute, Event)> {
+    let index = CLAIM_COUNT.may_load(storage)?.unwrap_or_default();
+    let contract = env.contract.address.as_str();
+    let event = match kind {
+        ClaimKind::Principal => events::vesting_claimed(contract, owner, &denom, recipient, amount),
+        ClaimKind::Rewards => events::rewards_claimed(contract, owner, &denom, recipient, amount),
+    };
+
+    CLAIM_HISTORY.save(
+        storage,
+        U64Key::new(index),
+        &ClaimRecord {
+            index,
+            time: env.block.time.seconds(),
+            kind,
+            recipient: recipient.to_string(),
+            denom,
+            amount,
+        },
+    )?;
+    CLAIM_COUNT.save(storage, &(index + 1))?;
+
+    Ok((
+        Attribute::new("claim_index", index.to_string()),
+        event.add_attribute("claim_index", index.to_string()),
+    ))
+}
+
+/// Append a claim of uusd rewards to the history
+pub(crate) fn record_rewards_claim(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &str,
+    recipient: &str,
+    amount: Uint128,
+) -> StdResult<(Attribute, Event)> {
+    record_claim(
+        storage,
+        env,
+        ClaimKind::Rewards,
+        owner,
+        recipient,
+        Denom::Native(REWARDS_DENOM.to_string()),
+        amount,
+    )
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub(crate) fn query_claim_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ClaimHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|index| Bound::exclusive(U64Key::new(index)));
+
+    let claims = CLAIM_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<ClaimRecord>>>()?;
+
+    Ok(ClaimHistoryResponse { claims })
+}
@@ -1,9 +1,12 @@
+mod compound;
 pub mod contract;
 mod external;
+mod history;
+mod rewards;
+mod shared;
+mod solvency;
 pub mod state;
+mod suspension;
 
 #[cfg(test)]
 mod testing;
-
-#[cfg(test)]
-mod mock_querier;
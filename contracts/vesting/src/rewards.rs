@@ -0,0 +1,421 @@
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Uint128, WasmMsg,
+};
+
+use common::error::ContractError;
+use common::staking::{AccruedRewardsResponse, RewardContractQueryMsg};
+use common::tax::deduct_tax;
+use common::vesting::{ClaimKind, RewardsResponse, RewardsStrategy};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Denom};
+
+use crate::contract::{
+    check_permission, check_recipient, rewards_transfer_message, withdraw_rewards_message,
+    REWARDS_DENOM,
+};
+use crate::external::handle::MarketExecuteMsg;
+use crate::history::{record_claim, record_rewards_claim};
+use crate::shared::is_shared;
+use crate::state::{
+    Config, RewardHolder, RewardState, VestingInfo, CONFIG, REWARD_HOLDERS, REWARD_STATE,
+    VESTING_INFO,
+};
+
+/// Rewards balance of this contract, except the uusd held
+/// as the rewards position
+fn rewards_balance(deps: Deps, env: &Env) -> StdResult<Uint128> {
+    let balance: Coin = deps
+        .querier
+        .query_balance(env.contract.address.to_string(), REWARDS_DENOM)?;
+    let state = REWARD_STATE.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(balance.amount.checked_sub(state.rewards_held)?)
+}
+
+/// Rewards balance of this contract and rewards accrued at the reward contract
+pub(crate) fn query_rewards(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> StdResult<(Uint128, Uint128)> {
+    let staking_info = config.staking_info.clone().unwrap();
+    let existing_rewards = rewards_balance(deps, env)?;
+    let response: AccruedRewardsResponse = deps.querier.query_wasm_smart(
+        Addr::unchecked(staking_info.reward_contract),
+        &RewardContractQueryMsg::AccruedRewards {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+
+    Ok((existing_rewards, response.rewards))
+}
+
+/// Compute the reward index and the position of the current owner
+/// at this block without storing them
+pub(crate) fn owner_rewards(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> StdResult<(RewardState, RewardHolder)> {
+    let (existing_rewards, accrued_rewards) = query_rewards(deps, env, config)?;
+    index_owner_rewards(deps, config, existing_rewards + accrued_rewards)
+}
+
+/// Advance the reward index to `rewards` held or accrued by this contract
+fn index_owner_rewards(
+    deps: Deps,
+    config: &Config,
+    rewards: Uint128,
+) -> StdResult<(RewardState, RewardHolder)> {
+    // only unclaimed bLUNA earns rewards
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let staked_amount = vesting_info
+        .total_amount()
+        .checked_sub(vesting_info.claimed_amount)?;
+
+    let mut state = REWARD_STATE.may_load(deps.storage)?.unwrap_or_default();
+    state.update_reward_index(state.rewards_paid + rewards, staked_amount)?;
+
+    let mut holder = REWARD_HOLDERS
+        .may_load(deps.storage, config.owner_address.to_string())?
+        .unwrap_or_default();
+    holder.settle(state.reward_index, staked_amount);
+
+    // rewards left unsettled until everything is claimed belong to the owner
+    if staked_amount.is_zero() {
+        let remaining_rewards =
+            (state.rewards_paid + rewards).checked_sub(state.rewards_checkpoint)?;
+        holder.pending_rewards += remaining_rewards;
+        state.rewards_checkpoint += remaining_rewards;
+    }
+
+    Ok((state, holder))
+}
+
+/// Pending rewards of the owner, or of all beneficiaries when shared
+pub(crate) fn claimable_rewards(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint128> {
+    if is_shared(deps.storage)? {
+        let (existing_rewards, accrued_rewards) = query_rewards(deps, env, config)?;
+        return Ok(existing_rewards + accrued_rewards);
+    }
+
+    let (_, holder) = owner_rewards(deps, env, config)?;
+    Ok(holder.pending_rewards)
+}
+
+pub(crate) fn query_rewards_info(deps: Deps, env: Env) -> Result<RewardsResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if !config.staking_enabled {
+        return Err(ContractError::StakingDisabled {});
+    }
+
+    Ok(RewardsResponse {
+        claimable_staking_rewards: claimable_rewards(deps, &env, &config)?,
+        rewards_position: rewards_position(deps, &env, &config)?,
+    })
+}
+
+/// Accrue rewards of the current owner up to this block and return
+/// the owner's pending rewards. This must run before the unclaimed
+/// bLUNA amount or the owner changes.
+pub(crate) fn settle_owner_rewards(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+) -> StdResult<Uint128> {
+    let (state, holder) = owner_rewards(deps.as_ref(), env, config)?;
+    save_owner_rewards(deps, config, state, holder)
+}
+
+/// Settle the owner's rewards like `settle_owner_rewards` unless the reward
+/// contract cannot be queried. The index is then left unsettled, and rewards
+/// accrued since the last settlement are counted at the next one, so
+/// principal claims do not depend on the reward contract.
+pub(crate) fn try_settle_owner_rewards(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+) -> StdResult<Option<Uint128>> {
+    let (existing_rewards, accrued_rewards) = match query_rewards(deps.as_ref(), env, config) {
+        Ok(rewards) => rewards,
+        Err(_) => return Ok(None),
+    };
+
+    let (state, holder) =
+        index_owner_rewards(deps.as_ref(), config, existing_rewards + accrued_rewards)?;
+    save_owner_rewards(deps, config, state, holder).map(Some)
+}
+
+fn save_owner_rewards(
+    deps: DepsMut,
+    config: &Config,
+    state: RewardState,
+    holder: RewardHolder,
+) -> StdResult<Uint128> {
+    REWARD_STATE.save(deps.storage, &state)?;
+    REWARD_HOLDERS.save(deps.storage, config.owner_address.to_string(), &holder)?;
+
+    Ok(holder.pending_rewards)
+}
+
+/// Deduct `amount` from the pending rewards of `holder`. Returns the message
+/// withdrawing accrued rewards to this contract when the balance is not enough.
+pub(crate) fn take_rewards(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    holder: &str,
+    amount: Uint128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    let mut reward_holder = REWARD_HOLDERS
+        .may_load(deps.storage, holder.to_string())?
+        .unwrap_or_default();
+    reward_holder.pending_rewards = reward_holder
+        .pending_rewards
+        .checked_sub(amount)
+        .map_err(|_| ContractError::ClaimAmountExceeded {})?;
+
+    // previous owners are removed once everything is paid
+    if reward_holder.pending_rewards.is_zero() && holder != config.owner_address {
+        REWARD_HOLDERS.remove(deps.storage, holder.to_string());
+    } else {
+        REWARD_HOLDERS.save(deps.storage, holder.to_string(), &reward_holder)?;
+    }
+
+    let mut state = REWARD_STATE.may_load(deps.storage)?.unwrap_or_default();
+    state.rewards_paid += amount;
+    REWARD_STATE.save(deps.storage, &state)?;
+
+    if rewards_balance(deps.as_ref(), env)? >= amount {
+        return Ok(None);
+    }
+
+    let staking_info = config.staking_info.clone().unwrap();
+    Ok(Some(withdraw_rewards_message(
+        staking_info.reward_contract,
+    )?))
+}
+
+/// Pay a previous owner the rewards settled to it on ownership transfer
+pub(crate) fn claim_settled_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = info.sender.to_string();
+    let recipient = recipient.unwrap_or_else(|| sender.to_string());
+    deps.api.addr_validate(&recipient)?;
+
+    let reward_holder = REWARD_HOLDERS.load(deps.storage, sender.to_string())?;
+    let rewards_claim_amount = reward_holder.pending_rewards;
+    if rewards_claim_amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    messages.extend(take_rewards(
+        deps.branch(),
+        &env,
+        &config,
+        &sender,
+        rewards_claim_amount,
+    )?);
+    messages.push(rewards_transfer_message(
+        &deps.querier,
+        recipient.to_string(),
+        rewards_claim_amount,
+    )?);
+    let (claim_index, event) = record_rewards_claim(
+        deps.storage,
+        &env,
+        &sender,
+        &recipient,
+        rewards_claim_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "claim_rewards"),
+            ("previous_owner", sender.as_str()),
+            ("recipient", recipient.as_str()),
+            ("rewards_claim_amount", &rewards_claim_amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
+
+/// Keep `amount` of the owner's pending rewards as the rewards position
+/// following `rewards_strategy`, instead of paying them out
+pub(crate) fn store_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    config: Config,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !amount.is_zero() {
+        messages.extend(take_rewards(
+            deps.branch(),
+            &env,
+            &config,
+            &config.owner_address,
+            amount,
+        )?);
+
+        match &config.rewards_strategy {
+            RewardsStrategy::DepositToMarket {
+                market_contract, ..
+            } => messages.push(
+                WasmMsg::Execute {
+                    contract_addr: market_contract.to_string(),
+                    msg: to_binary(&MarketExecuteMsg::DepositStable {})?,
+                    funds: vec![deduct_tax(
+                        &deps.querier,
+                        Coin {
+                            denom: REWARDS_DENOM.to_string(),
+                            amount,
+                        },
+                    )?],
+                }
+                .into(),
+            ),
+            _ => {
+                let mut state = REWARD_STATE.load(deps.storage)?;
+                state.rewards_held += amount;
+                REWARD_STATE.save(deps.storage, &state)?;
+            }
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "claim_rewards"),
+        ("rewards_position_amount", &amount.to_string()),
+    ]))
+}
+
+/// uusd held or aUST deposited as the rewards position
+pub(crate) fn rewards_position(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint128> {
+    match &config.rewards_strategy {
+        RewardsStrategy::DepositToMarket { aterra_token, .. } => {
+            let response: BalanceResponse = deps.querier.query_wasm_smart(
+                Addr::unchecked(aterra_token),
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(response.balance)
+        }
+        _ => Ok(REWARD_STATE
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .rewards_held),
+    }
+}
+
+pub(crate) fn set_rewards_strategy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rewards_strategy: RewardsStrategy,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !config.staking_enabled {
+        return Err(ContractError::StakingDisabled {});
+    }
+
+    if !rewards_position(deps.as_ref(), &env, &config)?.is_zero() {
+        return Err(ContractError::RewardsPositionOpen {});
+    }
+
+    if let RewardsStrategy::DepositToMarket {
+        market_contract,
+        aterra_token,
+    } = &rewards_strategy
+    {
+        deps.api.addr_validate(market_contract)?;
+        deps.api.addr_validate(aterra_token)?;
+    }
+
+    config.rewards_strategy = rewards_strategy;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "set_rewards_strategy"))
+}
+
+pub(crate) fn withdraw_rewards_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let is_operator = check_permission(deps.as_ref(), &env, &config, &info.sender, |p| {
+        p.claim_rewards
+    })?;
+
+    let recipient = recipient
+        .or_else(|| config.claim_preferences.rewards_recipient.clone())
+        .unwrap_or_else(|| config.owner_address.to_string());
+    deps.api.addr_validate(&recipient)?;
+    check_recipient(&config, is_operator, &recipient)?;
+
+    let amount = rewards_position(deps.as_ref(), &env, &config)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+
+    let (message, (claim_index, event)): (CosmosMsg, _) = match &config.rewards_strategy {
+        RewardsStrategy::DepositToMarket { aterra_token, .. } => (
+            WasmMsg::Execute {
+                contract_addr: aterra_token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+            record_claim(
+                deps.storage,
+                &env,
+                ClaimKind::Rewards,
+                &config.owner_address,
+                &recipient,
+                Denom::Cw20(Addr::unchecked(aterra_token)),
+                amount,
+            )?,
+        ),
+        _ => {
+            let mut state = REWARD_STATE.load(deps.storage)?;
+            state.rewards_held = Uint128::zero();
+            REWARD_STATE.save(deps.storage, &state)?;
+            (
+                rewards_transfer_message(&deps.querier, recipient.to_string(), amount)?,
+                record_rewards_claim(
+                    deps.storage,
+                    &env,
+                    &config.owner_address,
+                    &recipient,
+                    amount,
+                )?,
+            )
+        }
+    };
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            ("action", "withdraw_rewards_position"),
+            ("recipient", recipient.as_str()),
+            ("withdraw_amount", &amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
@@ -0,0 +1,490 @@
+use cosmwasm_std::{
+    CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use common::error::ContractError;
+use common::vesting::{
+    claim_amount, transfer_message, BeneficiariesResponse, BeneficiaryResponse, BeneficiaryShare,
+    ClaimKind,
+};
+
+use crate::contract::{rewards_transfer_message, withdraw_rewards_message};
+use crate::history::{record_claim, record_rewards_claim};
+use crate::rewards::query_rewards;
+use crate::solvency::payout_amount;
+use crate::state::{
+    Beneficiary, Config, SharedState, VestingInfo, BENEFICIARIES, CONFIG, SHARED_STATE,
+    VESTING_INFO,
+};
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Store beneficiaries of a shared vesting contract
+pub fn instantiate_beneficiaries(
+    mut deps: DepsMut,
+    beneficiaries: Vec<BeneficiaryShare>,
+) -> Result<(), ContractError> {
+    let total_share = save_shares(deps.branch(), beneficiaries, Decimal::zero())?;
+    SHARED_STATE.save(
+        deps.storage,
+        &SharedState {
+            total_share,
+            vested_checkpoint: Uint128::zero(),
+            reward_index: Decimal::zero(),
+            rewards_checkpoint: Uint128::zero(),
+            rewards_paid: Uint128::zero(),
+        },
+    )?;
+
+    Ok(())
+}
+
+pub fn is_shared(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(SHARED_STATE.may_load(storage)?.is_some())
+}
+
+/// Reassign shares. What each beneficiary has accrued so far is settled
+/// first, so the new shares only apply to the amounts vested from now on.
+/// Rewards follow the unclaimed principal under the new shares.
+pub fn update_beneficiaries(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    beneficiaries: Vec<BeneficiaryShare>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (mut state, vesting_info, _) = load_totals(deps.as_ref(), &env)?;
+    let vested_amount = vesting_info.vested_amount(env.block.time.seconds())?;
+
+    // settle and clear all existing shares
+    for address in beneficiary_addresses(deps.storage)? {
+        let mut beneficiary = BENEFICIARIES.load(deps.storage, address.to_string())?;
+        beneficiary.settle_rewards(&state, vesting_info.vesting_amount)?;
+        beneficiary.settled_amount = beneficiary
+            .vested_amount(&state, vested_amount)?
+            .max(beneficiary.claimed_amount);
+        beneficiary.share = Uint128::zero();
+        BENEFICIARIES.save(deps.storage, address, &beneficiary)?;
+    }
+
+    state.total_share = save_shares(deps.branch(), beneficiaries, state.reward_index)?;
+    state.vested_checkpoint = vested_amount;
+    SHARED_STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_beneficiaries"),
+        ("total_share", &state.total_share.to_string()),
+    ]))
+}
+
+/// Accrue rewards of every beneficiary up to this block. This must run
+/// before the vesting amount changes, since it changes what each
+/// beneficiary holds.
+pub fn settle_rewards(deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+    let (state, vesting_info, _) = load_totals(deps.as_ref(), env)?;
+    for address in beneficiary_addresses(deps.storage)? {
+        let mut beneficiary = BENEFICIARIES.load(deps.storage, address.to_string())?;
+        beneficiary.settle_rewards(&state, vesting_info.vesting_amount)?;
+        BENEFICIARIES.save(deps.storage, address, &beneficiary)?;
+    }
+
+    SHARED_STATE.save(deps.storage, &state)?;
+    Ok(())
+}
+
+fn beneficiary_addresses(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    BENEFICIARIES
+        .keys(storage, None, None, Order::Ascending)
+        .map(String::from_utf8)
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(StdError::from)
+}
+
+/// Claim the sender's part of the vested amount
+pub fn claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+    amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let sender = info.sender.to_string();
+    let mut beneficiary = load_beneficiary(deps.storage, &sender)?;
+    let recipient = recipient.unwrap_or_else(|| sender.to_string());
+    deps.api.addr_validate(&recipient)?;
+
+    // rewards of the principal claimed now are settled first
+    let (state, vesting_info, _) = load_totals(deps.as_ref(), &env)?;
+    beneficiary.settle_rewards(&state, vesting_info.vesting_amount)?;
+    let claimers = PendingClaimers::load(deps.storage, &state, vesting_info.vesting_amount)?;
+    let vested_amount = vested_principal(
+        &beneficiary,
+        &state,
+        &vesting_info,
+        env.block.time.seconds(),
+        claimers.is_last_principal_claimer(&sender),
+    )?;
+
+    let claim_amount = claim_amount(
+        vested_amount.checked_sub(beneficiary.claimed_amount)?,
+        amount,
+    )?;
+
+    let payout_amount = payout_amount(deps.as_ref(), &env, &vesting_info, claim_amount)?;
+
+    beneficiary.claimed_amount += claim_amount;
+    BENEFICIARIES.save(deps.storage, sender.to_string(), &beneficiary)?;
+    SHARED_STATE.save(deps.storage, &state)?;
+
+    let vesting_info = VESTING_INFO.update(deps.storage, |mut v| -> StdResult<_> {
+        v.claimed_amount = v.claimed_amount.checked_add(claim_amount)?;
+        Ok(v)
+    })?;
+
+    let message = transfer_message(
+        &deps.querier,
+        &vesting_info.vesting_denom,
+        recipient.clone(),
+        payout_amount,
+    )?;
+    let (claim_index, event) = record_claim(
+        deps.storage,
+        &env,
+        ClaimKind::Principal,
+        &sender,
+        &recipient,
+        vesting_info.vesting_denom.clone(),
+        payout_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_message(message)
+        .add_attributes(vec![
+            ("action", "claim"),
+            ("beneficiary", sender.as_str()),
+            ("recipient", recipient.as_str()),
+            ("vested_amount", &vested_amount.to_string()),
+            ("claim_amount", &payout_amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
+
+/// Claim the sender's part of the staking rewards
+pub fn claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let sender = info.sender.to_string();
+    let mut beneficiary = load_beneficiary(deps.storage, &sender)?;
+    let recipient = recipient.unwrap_or_else(|| sender.to_string());
+    deps.api.addr_validate(&recipient)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    if !config.staking_enabled {
+        return Err(ContractError::StakingDisabled {});
+    }
+
+    let mut state = SHARED_STATE.load(deps.storage)?;
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let (existing_rewards, accrued_rewards) = query_rewards(deps.as_ref(), &env, &config)?;
+    let total_rewards = state.rewards_paid + existing_rewards + accrued_rewards;
+    state.update_reward_index(total_rewards, unclaimed_principal(&vesting_info)?)?;
+    beneficiary.settle_rewards(&state, vesting_info.vesting_amount)?;
+
+    // the last one to claim also takes the rounding remainders
+    let mut rewards_claim_amount = beneficiary.claimable_rewards()?;
+    let claimers = PendingClaimers::load(deps.storage, &state, vesting_info.vesting_amount)?;
+    if claimers.is_last_claimer(&sender) {
+        rewards_claim_amount = total_rewards.checked_sub(state.rewards_paid)?;
+        beneficiary.settled_rewards = beneficiary.claimed_rewards + rewards_claim_amount;
+        state.rewards_checkpoint = total_rewards;
+    }
+    if rewards_claim_amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    beneficiary.claimed_rewards += rewards_claim_amount;
+    BENEFICIARIES.save(deps.storage, sender.to_string(), &beneficiary)?;
+
+    state.rewards_paid += rewards_claim_amount;
+    SHARED_STATE.save(deps.storage, &state)?;
+
+    // withdraw accrued rewards to this contract when the balance is not enough
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if existing_rewards < rewards_claim_amount {
+        let staking_info = config.staking_info.unwrap();
+        messages.push(withdraw_rewards_message(staking_info.reward_contract)?);
+    }
+
+    messages.push(rewards_transfer_message(
+        &deps.querier,
+        recipient.to_string(),
+        rewards_claim_amount,
+    )?);
+    let (claim_index, event) = record_rewards_claim(
+        deps.storage,
+        &env,
+        &sender,
+        &recipient,
+        rewards_claim_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "claim_rewards"),
+            ("beneficiary", sender.as_str()),
+            ("recipient", recipient.as_str()),
+            ("rewards_claim_amount", &rewards_claim_amount.to_string()),
+        ])
+        .add_attribute(claim_index.key, claim_index.value)
+        .add_event(event))
+}
+
+pub fn query_beneficiary(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> Result<BeneficiaryResponse, ContractError> {
+    let beneficiary = load_beneficiary(deps.storage, &address)?;
+    let (state, vesting_info, total_rewards) = load_totals(deps, &env)?;
+    let claimers = PendingClaimers::load(deps.storage, &state, vesting_info.vesting_amount)?;
+
+    Ok(beneficiary_response(
+        &claimers,
+        &env,
+        address,
+        beneficiary,
+        &state,
+        &vesting_info,
+        total_rewards,
+    )?)
+}
+
+pub fn query_beneficiaries(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<BeneficiariesResponse, ContractError> {
+    let (state, vesting_info, total_rewards) = load_totals(deps, &env)?;
+    let claimers = PendingClaimers::load(deps.storage, &state, vesting_info.vesting_amount)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let beneficiaries = BENEFICIARIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, beneficiary) = item?;
+            beneficiary_response(
+                &claimers,
+                &env,
+                String::from_utf8(address)?,
+                beneficiary,
+                &state,
+                &vesting_info,
+                total_rewards,
+            )
+        })
+        .collect::<StdResult<Vec<BeneficiaryResponse>>>()?;
+
+    Ok(BeneficiariesResponse { beneficiaries })
+}
+
+fn beneficiary_response(
+    claimers: &PendingClaimers,
+    env: &Env,
+    address: String,
+    mut beneficiary: Beneficiary,
+    state: &SharedState,
+    vesting_info: &VestingInfo,
+    total_rewards: Uint128,
+) -> StdResult<BeneficiaryResponse> {
+    beneficiary.settle_rewards(state, vesting_info.vesting_amount)?;
+    let vested_amount = vested_principal(
+        &beneficiary,
+        state,
+        vesting_info,
+        env.block.time.seconds(),
+        claimers.is_last_principal_claimer(&address),
+    )?;
+    let claimable_rewards = if claimers.is_last_claimer(&address) {
+        total_rewards.checked_sub(state.rewards_paid)?
+    } else {
+        beneficiary.claimable_rewards()?
+    };
+
+    Ok(BeneficiaryResponse {
+        address,
+        share: beneficiary.share,
+        vested_amount,
+        claimed_amount: beneficiary.claimed_amount,
+        claimable_amount: vested_amount.checked_sub(beneficiary.claimed_amount)?,
+        claimed_staking_rewards: beneficiary.claimed_rewards,
+        claimable_staking_rewards: claimable_rewards,
+    })
+}
+
+/// Load the shared state with `reward_index` moved forward to the
+/// current rewards, which are returned too. The caller stores the state.
+fn load_totals(
+    deps: Deps,
+    env: &Env,
+) -> Result<(SharedState, VestingInfo, Uint128), ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut state = match SHARED_STATE.may_load(deps.storage)? {
+        Some(state) => state,
+        None => return Err(ContractError::NotShared {}),
+    };
+
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let total_rewards = total_rewards(deps, env, &config, &state)?;
+    state.update_reward_index(total_rewards, unclaimed_principal(&vesting_info)?)?;
+    Ok((state, vesting_info, total_rewards))
+}
+
+/// Principal of all beneficiaries not claimed yet, which earns the rewards
+fn unclaimed_principal(vesting_info: &VestingInfo) -> StdResult<Uint128> {
+    Ok(vesting_info
+        .vesting_amount
+        .checked_sub(vesting_info.claimed_amount)?)
+}
+
+/// Principal vested for `beneficiary` at `time`. Shares of the vested amount
+/// are rounded down, so the last one to claim principal takes the remainder.
+fn vested_principal(
+    beneficiary: &Beneficiary,
+    state: &SharedState,
+    vesting_info: &VestingInfo,
+    time: u64,
+    is_last_claimer: bool,
+) -> StdResult<Uint128> {
+    let vested_amount = vesting_info.vested_amount(time)?;
+    if !is_last_claimer {
+        return beneficiary.vested_amount(state, vested_amount);
+    }
+
+    // everything vested except what the others have claimed
+    let claimed_by_others = vesting_info
+        .claimed_amount
+        .checked_sub(beneficiary.claimed_amount)?;
+    Ok(vested_amount.checked_sub(claimed_by_others)?)
+}
+
+/// Beneficiaries still owed principal, and those still owed principal or
+/// rewards. Two of each are enough to tell whether a beneficiary is the
+/// last claimer, so a single scan answers it for all beneficiaries.
+struct PendingClaimers {
+    principal: Vec<Vec<u8>>,
+    any: Vec<Vec<u8>>,
+}
+
+impl PendingClaimers {
+    fn load(
+        storage: &dyn Storage,
+        state: &SharedState,
+        vesting_amount: Uint128,
+    ) -> StdResult<Self> {
+        let mut claimers = PendingClaimers {
+            principal: vec![],
+            any: vec![],
+        };
+        for item in BENEFICIARIES.range(storage, None, None, Order::Ascending) {
+            let (address, beneficiary) = item?;
+            let owes_principal = !beneficiary
+                .unclaimed_amount(state, vesting_amount)?
+                .is_zero();
+            if owes_principal && claimers.principal.len() < 2 {
+                claimers.principal.push(address.clone());
+            }
+            if (owes_principal || !beneficiary.claimable_rewards()?.is_zero())
+                && claimers.any.len() < 2
+            {
+                claimers.any.push(address);
+            }
+            if claimers.principal.len() == 2 {
+                break;
+            }
+        }
+
+        Ok(claimers)
+    }
+
+    /// Whether every beneficiary other than `address` has claimed all of its principal
+    fn is_last_principal_claimer(&self, address: &str) -> bool {
+        self.principal.iter().all(|a| a == address.as_bytes())
+    }
+
+    /// Whether every beneficiary other than `address` has claimed
+    /// all of its principal and rewards
+    fn is_last_claimer(&self, address: &str) -> bool {
+        self.any.iter().all(|a| a == address.as_bytes())
+    }
+}
+
+fn load_beneficiary(storage: &dyn Storage, address: &str) -> Result<Beneficiary, ContractError> {
+    BENEFICIARIES
+        .may_load(storage, address.to_string())?
+        .ok_or(ContractError::Unauthorized {})
+}
+
+/// Save shares and return the total share
+fn save_shares(
+    deps: DepsMut,
+    beneficiaries: Vec<BeneficiaryShare>,
+    reward_index: Decimal,
+) -> Result<Uint128, ContractError> {
+    let mut total_share = Uint128::zero();
+    for b in beneficiaries {
+        deps.api.addr_validate(&b.address)?;
+        if b.share.is_zero() {
+            return Err(ContractError::ZeroShare {});
+        }
+
+        let mut beneficiary = BENEFICIARIES
+            .may_load(deps.storage, b.address.to_string())?
+            .unwrap_or_default();
+        if !beneficiary.share.is_zero() {
+            return Err(ContractError::DuplicatedBeneficiary {
+                address: b.address.to_string(),
+            });
+        }
+
+        beneficiary.share = b.share;
+        beneficiary.reward_index = reward_index;
+        total_share = total_share.checked_add(b.share)?;
+        BENEFICIARIES.save(deps.storage, b.address, &beneficiary)?;
+    }
+
+    if total_share.is_zero() {
+        return Err(ContractError::EmptyBeneficiaries {});
+    }
+
+    Ok(total_share)
+}
+
+/// Rewards ever received by this contract, including the ones paid out
+fn total_rewards(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    state: &SharedState,
+) -> StdResult<Uint128> {
+    if !config.staking_enabled {
+        return Ok(Uint128::zero());
+    }
+
+    let (existing_rewards, accrued_rewards) = query_rewards(deps, env, config)?;
+    Ok(state.rewards_paid + existing_rewards + accrued_rewards)
+}
@@ -0,0 +1,224 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128};
+
+use common::error::ContractError;
+use common::events::{denom_kind, denom_name};
+use common::vesting::{transfer_message, RewardsStrategy, SolvencyResponse};
+use cw20::{BalanceResponse, Cw20QueryMsg, Denom};
+
+use crate::contract::{check_recipient, REWARDS_DENOM};
+use crate::rewards::settle_owner_rewards;
+use crate::shared::{self, is_shared};
+use crate::state::{Config, VestingInfo, CONFIG, VESTING_INFO};
+
+/// Balance of `vesting_denom` held by this contract
+pub(crate) fn principal_balance(
+    deps: Deps,
+    env: &Env,
+    vesting_info: &VestingInfo,
+) -> StdResult<Uint128> {
+    denom_balance(deps, env, &vesting_info.vesting_denom)
+}
+
+fn denom_balance(deps: Deps, env: &Env, denom: &Denom) -> StdResult<Uint128> {
+    match denom {
+        Denom::Native(denom) => Ok(deps
+            .querier
+            .query_balance(env.contract.address.to_string(), denom)?
+            .amount),
+        Denom::Cw20(contract_addr) => {
+            let response: BalanceResponse = deps.querier.query_wasm_smart(
+                contract_addr.to_string(),
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(response.balance)
+        }
+    }
+}
+
+/// Principal balance and unclaimed amount, when the contract holds less
+/// than the unclaimed amount (e.g. the hub was slashed)
+pub(crate) fn deficit(
+    deps: Deps,
+    env: &Env,
+    vesting_info: &VestingInfo,
+) -> StdResult<Option<(Uint128, Uint128)>> {
+    let obligation = vesting_info
+        .total_amount()
+        .checked_sub(vesting_info.claimed_amount)?;
+    let balance = principal_balance(deps, env, vesting_info)?;
+    if balance >= obligation {
+        return Ok(None);
+    }
+
+    Ok(Some((balance, obligation)))
+}
+
+/// Tokens paid for `amount` of the unclaimed position. In a deficit,
+/// every claim is scaled down by the same ratio, so the last claim never fails.
+pub(crate) fn payout_amount(
+    deps: Deps,
+    env: &Env,
+    vesting_info: &VestingInfo,
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    Ok(scaled_amount(amount, deficit(deps, env, vesting_info)?))
+}
+
+/// `amount` scaled to the holdings in a `deficit`
+pub(crate) fn scaled_amount(amount: Uint128, deficit: Option<(Uint128, Uint128)>) -> Uint128 {
+    match deficit {
+        Some((balance, obligation)) => amount.multiply_ratio(balance, obligation),
+        None => amount,
+    }
+}
+
+/// `shares` of `amount` scaled to the `payout_amount` paid for it in a deficit.
+/// Rounding remainders go to the first share.
+pub(crate) fn scaled_shares(
+    mut shares: Vec<(String, Uint128)>,
+    amount: Uint128,
+    payout_amount: Uint128,
+) -> StdResult<Vec<(String, Uint128)>> {
+    if payout_amount == amount || shares.is_empty() {
+        return Ok(shares);
+    }
+
+    for (_, share) in shares.iter_mut() {
+        *share = share.multiply_ratio(payout_amount, amount);
+    }
+    let scaled: Uint128 = shares.iter().map(|(_, share)| *share).sum();
+    shares[0].1 += payout_amount.checked_sub(scaled)?;
+
+    Ok(shares)
+}
+
+pub(crate) fn query_solvency(deps: Deps, env: Env) -> StdResult<SolvencyResponse> {
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let balance = principal_balance(deps, &env, &vesting_info)?;
+    let obligation = vesting_info
+        .total_amount()
+        .checked_sub(vesting_info.claimed_amount)?;
+
+    Ok(SolvencyResponse {
+        vesting_denom: vesting_info.vesting_denom,
+        balance,
+        obligation,
+        surplus: balance.saturating_sub(obligation),
+        deficit: obligation.saturating_sub(balance),
+    })
+}
+
+/// Add principal held beyond the unclaimed amount to the vesting position.
+/// A deficit needs no reconciliation since claims are already scaled down.
+pub(crate) fn reconcile(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender && config.admin.as_deref() != Some(info.sender.as_str())
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let solvency = query_solvency(deps.as_ref(), env.clone())?;
+    if solvency.surplus.is_zero() {
+        return Err(ContractError::NoSurplus {});
+    }
+
+    // rewards accrued so far are for the position before it grows
+    if is_shared(deps.storage)? {
+        shared::settle_rewards(deps.branch(), &env)?;
+    } else if config.staking_enabled {
+        settle_owner_rewards(deps.branch(), &env, &config)?;
+    }
+
+    let vesting_info = VESTING_INFO.update(deps.storage, |mut v| -> StdResult<_> {
+        v.vesting_amount += solvency.surplus;
+        Ok(v)
+    })?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "reconcile"),
+        ("balance", &solvency.balance.to_string()),
+        ("surplus", &solvency.surplus.to_string()),
+        ("vesting_amount", &vesting_info.total_amount().to_string()),
+    ]))
+}
+
+/// Amount of `asset` that must stay in the contract: the unclaimed
+/// principal, and all uusd and aUST since they are rewards owed
+fn reserved_amount(
+    config: &Config,
+    vesting_info: &VestingInfo,
+    asset: &Denom,
+    balance: Uint128,
+) -> StdResult<Uint128> {
+    if *asset == vesting_info.vesting_denom {
+        return Ok(vesting_info
+            .total_amount()
+            .checked_sub(vesting_info.claimed_amount)?);
+    }
+
+    let is_rewards = match (asset, &config.rewards_strategy) {
+        (Denom::Native(denom), _) => config.staking_enabled && denom == REWARDS_DENOM,
+        (Denom::Cw20(token), RewardsStrategy::DepositToMarket { aterra_token, .. }) => {
+            token.as_str() == aterra_token
+        }
+        _ => false,
+    };
+
+    Ok(if is_rewards { balance } else { Uint128::zero() })
+}
+
+/// Send tokens sent to the contract by mistake to `recipient`.
+/// Only the amount beyond what is reserved for the owner can be swept.
+pub(crate) fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Denom,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.owner_address != info.sender && config.funder != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = recipient.unwrap_or_else(|| info.sender.to_string());
+    deps.api.addr_validate(&recipient)?;
+    // the funder gets back what it sent by mistake, anyone else must be allowlisted
+    if recipient != config.funder {
+        check_recipient(&config, false, &recipient)?;
+    }
+
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    let balance = denom_balance(deps.as_ref(), &env, &asset)?;
+    let reserved_amount = reserved_amount(&config, &vesting_info, &asset, balance)?;
+    let sweep_amount = balance.saturating_sub(reserved_amount);
+    if sweep_amount.is_zero() {
+        return Err(ContractError::NothingToSweep {});
+    }
+
+    // principal can never be swept
+    if asset == vesting_info.vesting_denom {
+        let obligation = vesting_info
+            .total_amount()
+            .checked_sub(vesting_info.claimed_amount)?;
+        if balance.checked_sub(sweep_amount)? < obligation {
+            return Err(ContractError::SweepTakesPrincipal {});
+        }
+    }
+
+    let message = transfer_message(&deps.querier, &asset, recipient.clone(), sweep_amount)?;
+
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        ("action", "sweep"),
+        ("recipient", recipient.as_str()),
+        ("asset_kind", denom_kind(&asset)),
+        ("asset", &denom_name(&asset)),
+        ("sweep_amount", &sweep_amount.to_string()),
+    ]))
+}
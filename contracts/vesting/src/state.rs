@@ -1,19 +1,48 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use common::vesting::{StakingInfo, VestingSchedule};
-use cosmwasm_std::Uint128;
-use cw20::Denom;
-use cw_storage_plus::Item;
+use common::vesting::{
+    ClaimPreferences, ClaimRecord, CompoundConfig, DistributionConfig, OperatorPermissions,
+    RewardsStrategy, StakingInfo, SuspendedPeriod, UnlockResponse, VestingSchedule,
+};
+use cosmwasm_std::{Decimal, StdResult, Uint128};
+use cw20::{Denom, Expiration};
+use cw_storage_plus::{Item, Map, U64Key};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const VESTING_INFO: Item<VestingInfo> = Item::new("vesting_info");
+pub const LAST_DISTRIBUTION: Item<u64> = Item::new("last_distribution");
+pub const OPERATORS: Map<String, Operator> = Map::new("operators");
+pub const SHARED_STATE: Item<SharedState> = Item::new("shared_state");
+pub const BENEFICIARIES: Map<String, Beneficiary> = Map::new("beneficiaries");
+pub const REWARD_STATE: Item<RewardState> = Item::new("reward_state");
+pub const REWARD_HOLDERS: Map<String, RewardHolder> = Map::new("reward_holders");
+pub const TMP_SWAP: Item<TmpSwap> = Item::new("tmp_swap");
+pub const TMP_COMPOUND: Item<Uint128> = Item::new("tmp_compound");
+pub const CLAIM_COUNT: Item<u64> = Item::new("claim_count");
+pub const CLAIM_HISTORY: Map<U64Key, ClaimRecord> = Map::new("claim_history");
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
     pub owner_address: String,
     pub staking_enabled: bool,
     pub staking_info: Option<StakingInfo>,
+    pub claim_preferences: ClaimPreferences,
+    pub distribution: DistributionConfig,
+    pub compounding: Option<CompoundConfig>,
+    pub rewards_strategy: RewardsStrategy,
+    /// Allowed to sweep stray tokens along with the owner
+    pub funder: String,
+    /// Allowed to suspend and resume along with the funder
+    pub admin: Option<String>,
+}
+
+/// Kept from `Compound` to its swap reply
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TmpSwap {
+    /// uluna held before the swap, which is not bonded
+    pub uluna_balance: Uint128,
+    pub minimum_receive: Option<Uint128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -23,4 +52,285 @@ pub struct VestingInfo {
     pub vesting_amount: Uint128,
     pub vesting_schedule: VestingSchedule,
     pub claimed_amount: Uint128,
+    /// Compounded amount vested immediately
+    pub compounded_amount: Uint128,
+    /// Hub exchange rate when bLUNA was bonded, averaged over compounding
+    pub bond_exchange_rate: Option<Decimal>,
+    /// Allowed to start a deferred vesting, cleared once started.
+    /// Until then `vesting_schedule` is relative to the start.
+    pub start_trigger: Option<String>,
+    /// Periods the vesting did not advance, the last one open while suspended
+    pub suspended_periods: Vec<SuspendedPeriod>,
+}
+
+impl VestingInfo {
+    /// Amount vested at `block_time`, including the compounded amount.
+    /// Nothing is vested, compounded or not, until the vesting is started.
+    pub fn vested_amount(&self, block_time: u64) -> StdResult<Uint128> {
+        if self.is_pending_start() {
+            return Ok(Uint128::zero());
+        }
+
+        // the schedule stands still while suspended
+        let schedule_time = block_time.saturating_sub(self.suspended_duration(block_time));
+        Ok(self
+            .vesting_schedule
+            .vested_amount(schedule_time, self.vesting_amount)?
+            + self.compounded_amount)
+    }
+
+    /// `vesting_schedule` pushed back by the time suspended before `block_time`
+    pub fn effective_schedule(&self, block_time: u64) -> StdResult<VestingSchedule> {
+        self.vesting_schedule
+            .shifted(self.suspended_duration(block_time))
+    }
+
+    /// Seconds suspended before `block_time`
+    pub fn suspended_duration(&self, block_time: u64) -> u64 {
+        self.suspended_periods
+            .iter()
+            .map(|period| {
+                let end_time = period.end_time.unwrap_or(block_time).min(block_time);
+                end_time.saturating_sub(period.start_time)
+            })
+            .sum()
+    }
+
+    /// Time at which the schedule reaches `schedule_time`, pushed back by
+    /// each suspended period before it. `None` when it falls after the
+    /// start of the open suspended period.
+    fn unlock_time(&self, schedule_time: u64) -> Option<u64> {
+        let mut time = schedule_time;
+        for period in self.suspended_periods.iter() {
+            if period.start_time < time {
+                time = time.checked_add(period.end_time? - period.start_time)?;
+            }
+        }
+
+        Some(time)
+    }
+
+    /// Whether the last suspended period is still open
+    pub fn is_suspended(&self) -> bool {
+        matches!(
+            self.suspended_periods.last(),
+            Some(SuspendedPeriod { end_time: None, .. })
+        )
+    }
+
+    /// Whether the vesting waits for `StartVesting`
+    pub fn is_pending_start(&self) -> bool {
+        self.start_trigger.is_some()
+    }
+
+    /// Total amount of the vesting position
+    pub fn total_amount(&self) -> Uint128 {
+        self.vesting_amount + self.compounded_amount
+    }
+
+    /// Unlock events after `start_after`. Amounts are derived from
+    /// `vested_amount`, so they always agree with claims. There are none
+    /// until the vesting is started, nor after an ongoing suspension began.
+    pub fn unlocks(
+        &self,
+        start_after: Option<u64>,
+    ) -> StdResult<impl Iterator<Item = StdResult<UnlockResponse>> + '_> {
+        let mut previous_amount = match start_after {
+            Some(time) => self.vested_amount(time)?,
+            None if self.is_pending_start() => Uint128::zero(),
+            None => self.compounded_amount,
+        };
+
+        // closed suspended periods push an unlock back by at most their sum
+        let closed_duration: u64 = self
+            .suspended_periods
+            .iter()
+            .filter_map(|period| Some(period.end_time? - period.start_time))
+            .sum();
+        let pending_start = self.is_pending_start();
+
+        Ok(self
+            .vesting_schedule
+            .unlock_times(start_after.and_then(|time| time.checked_sub(closed_duration)))
+            .filter(move |_| !pending_start)
+            .map_while(move |schedule_time| self.unlock_time(schedule_time))
+            .filter(move |time| start_after < Some(*time))
+            .map(move |time| -> StdResult<Option<UnlockResponse>> {
+                let cumulative_amount = self.vested_amount(time)?;
+                let amount = cumulative_amount.checked_sub(previous_amount)?;
+                previous_amount = cumulative_amount;
+
+                Ok(if amount.is_zero() {
+                    None
+                } else {
+                    Some(UnlockResponse {
+                        time,
+                        amount,
+                        cumulative_amount,
+                    })
+                })
+            })
+            .filter_map(|unlock| unlock.transpose()))
+    }
+
+    /// Average `bond_exchange_rate` with `amount` bonded at `exchange_rate`,
+    /// before `amount` is added to the position
+    pub fn add_bond_exchange_rate(&mut self, exchange_rate: Decimal, amount: Uint128) {
+        let total_amount = self.total_amount();
+        self.bond_exchange_rate = Some(match self.bond_exchange_rate {
+            Some(bond_exchange_rate) if !total_amount.is_zero() => Decimal::from_ratio(
+                total_amount * bond_exchange_rate + amount * exchange_rate,
+                total_amount + amount,
+            ),
+            _ => exchange_rate,
+        });
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Operator {
+    pub permissions: OperatorPermissions,
+    pub expires: Expiration,
+}
+
+/// SharedState is used to split vesting amount and staking rewards
+/// among beneficiaries. Whenever shares are reassigned, what each
+/// beneficiary has vested so far is settled and the checkpoint moves,
+/// so only the amount vested after the checkpoint is split by current share.
+/// Staking rewards follow the principal each beneficiary still holds,
+/// through `reward_index`, the rewards accumulated per unclaimed bLUNA.
+/// `rewards_checkpoint` is the part of the total rewards (paid + balance
+/// + accrued) already reflected in the index.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct SharedState {
+    pub total_share: Uint128,
+    pub vested_checkpoint: Uint128,
+    pub reward_index: Decimal,
+    pub rewards_checkpoint: Uint128,
+    pub rewards_paid: Uint128,
+}
+
+impl SharedState {
+    /// Move `reward_index` forward to `total_rewards` earned on `unclaimed_amount`.
+    /// Rounding remainders are left for the next update.
+    pub fn update_reward_index(
+        &mut self,
+        total_rewards: Uint128,
+        unclaimed_amount: Uint128,
+    ) -> StdResult<()> {
+        if unclaimed_amount.is_zero() {
+            return Ok(());
+        }
+
+        let new_rewards = total_rewards.checked_sub(self.rewards_checkpoint)?;
+        let index_delta = Decimal::from_ratio(new_rewards, unclaimed_amount);
+        self.reward_index = self.reward_index + index_delta;
+        self.rewards_checkpoint += index_delta * unclaimed_amount;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct Beneficiary {
+    pub share: Uint128,
+    pub settled_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub reward_index: Decimal,
+    pub settled_rewards: Uint128,
+    pub claimed_rewards: Uint128,
+}
+
+impl Beneficiary {
+    /// Principal accrued up to `vested_amount` of the whole contract
+    pub fn vested_amount(&self, state: &SharedState, vested_amount: Uint128) -> StdResult<Uint128> {
+        Ok(self.settled_amount
+            + vested_amount
+                .checked_sub(state.vested_checkpoint)?
+                .multiply_ratio(self.share, state.total_share))
+    }
+
+    /// Principal of `vesting_amount` held for this beneficiary,
+    /// vested or not, which earns staking rewards. The last claimer
+    /// may have claimed the rounding remainder beyond its share.
+    pub fn unclaimed_amount(
+        &self,
+        state: &SharedState,
+        vesting_amount: Uint128,
+    ) -> StdResult<Uint128> {
+        Ok(self
+            .vested_amount(state, vesting_amount)?
+            .saturating_sub(self.claimed_amount))
+    }
+
+    /// Accrue rewards of the unclaimed principal up to `reward_index` of `state`.
+    /// Must run before the unclaimed principal changes.
+    pub fn settle_rewards(
+        &mut self,
+        state: &SharedState,
+        vesting_amount: Uint128,
+    ) -> StdResult<()> {
+        let unclaimed_amount = self.unclaimed_amount(state, vesting_amount)?;
+        self.settled_rewards += (state.reward_index - self.reward_index) * unclaimed_amount;
+        self.reward_index = state.reward_index;
+        Ok(())
+    }
+
+    /// Settled rewards not claimed yet
+    pub fn claimable_rewards(&self) -> StdResult<Uint128> {
+        Ok(self.settled_rewards.checked_sub(self.claimed_rewards)?)
+    }
+}
+
+/// RewardState tracks staking rewards per unclaimed bLUNA in the same way
+/// as Anchor's reward contract. `rewards_checkpoint` is the part of the
+/// total rewards (paid + balance + accrued) already reflected in the index.
+/// `rewards_held` is the uusd kept in this contract as the rewards position,
+/// which is already counted as paid.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardState {
+    pub reward_index: Decimal,
+    pub rewards_checkpoint: Uint128,
+    pub rewards_paid: Uint128,
+    pub rewards_held: Uint128,
+}
+
+impl RewardState {
+    /// Move `reward_index` forward to `total_rewards` earned on `staked_amount`.
+    /// Rounding remainders are left for the next update.
+    pub fn update_reward_index(
+        &mut self,
+        total_rewards: Uint128,
+        staked_amount: Uint128,
+    ) -> StdResult<()> {
+        if staked_amount.is_zero() {
+            return Ok(());
+        }
+
+        let new_rewards = total_rewards.checked_sub(self.rewards_checkpoint)?;
+        let index_delta = Decimal::from_ratio(new_rewards, staked_amount);
+        self.reward_index = self.reward_index + index_delta;
+        self.rewards_checkpoint += index_delta * staked_amount;
+        Ok(())
+    }
+}
+
+/// RewardHolder is the rewards position of an owner. Only the current
+/// owner accrues rewards, previous owners keep what was settled to them.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardHolder {
+    pub reward_index: Decimal,
+    pub pending_rewards: Uint128,
+}
+
+impl RewardHolder {
+    /// Accrue rewards of `staked_amount` up to `reward_index`
+    pub fn settle(&mut self, reward_index: Decimal, staked_amount: Uint128) {
+        self.pending_rewards += (reward_index - self.reward_index) * staked_amount;
+        self.reward_index = reward_index;
+    }
 }
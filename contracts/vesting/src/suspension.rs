@@ -0,0 +1,83 @@
+use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+
+use common::error::ContractError;
+use common::vesting::{SuspendedPeriod, SuspensionsResponse};
+
+use crate::state::{Config, VestingInfo, CONFIG, VESTING_INFO};
+
+fn check_suspender(config: &Config, info: &MessageInfo) -> Result<(), ContractError> {
+    if config.funder != info.sender && config.admin.as_deref() != Some(info.sender.as_str()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+/// Stop the vesting from advancing. What vested so far stays claimable.
+pub(crate) fn suspend(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    check_suspender(&config, &info)?;
+
+    let mut vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    if vesting_info.is_pending_start() {
+        return Err(ContractError::VestingNotStarted {});
+    }
+    if vesting_info.is_suspended() {
+        return Err(ContractError::AlreadySuspended {});
+    }
+
+    let block_time = env.block.time.seconds();
+    vesting_info.suspended_periods.push(SuspendedPeriod {
+        start_time: block_time,
+        end_time: None,
+    });
+    VESTING_INFO.save(deps.storage, &vesting_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "suspend"),
+        ("suspended_at", &block_time.to_string()),
+    ]))
+}
+
+/// Close the open suspended period, so the rest of the schedule
+/// is pushed back by its duration
+pub(crate) fn resume(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    check_suspender(&config, &info)?;
+
+    let mut vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+    if !vesting_info.is_suspended() {
+        return Err(ContractError::NotSuspended {});
+    }
+
+    let block_time = env.block.time.seconds();
+    let period = vesting_info.suspended_periods.last_mut().unwrap();
+    period.end_time = Some(block_time);
+    let suspended_duration = block_time - period.start_time;
+    VESTING_INFO.save(deps.storage, &vesting_info)?;
+
+    let schedule = vesting_info.effective_schedule(block_time)?;
+    Ok(Response::new().add_attributes(vec![
+        ("action", "resume"),
+        ("resumed_at", &block_time.to_string()),
+        ("suspended_duration", &suspended_duration.to_string()),
+        ("end_time", &schedule.end_time),
+    ]))
+}
+
+pub(crate) fn query_suspensions(deps: Deps, env: Env) -> StdResult<SuspensionsResponse> {
+    let vesting_info: VestingInfo = VESTING_INFO.load(deps.storage)?;
+
+    Ok(SuspensionsResponse {
+        suspended_duration: vesting_info.suspended_duration(env.block.time.seconds()),
+        suspended_periods: vesting_info.suspended_periods,
+    })
+}
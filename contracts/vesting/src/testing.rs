@@ -1,17 +1,26 @@
 use crate::contract::{execute, instantiate, query, reply};
-use crate::external::handle::{HubContractExecuteMsg, RewardContractExecuteMsg};
-use crate::mock_querier::mock_dependencies_with_querier;
+use crate::external::handle::{Asset, AssetInfo, MarketExecuteMsg, PairExecuteMsg};
+use common::error::ContractError;
+use common::mock_querier::mock_dependencies_with_querier;
+use common::staking::{HubContractExecuteMsg, RewardContractExecuteMsg};
 use common::vesting::{
-    ExecuteMsg, InstantiateMsg, QueryMsg, StakingInfo, VestingInfoResponse, VestingSchedule,
+    BeneficiariesResponse, BeneficiaryResponse, BeneficiaryShare, ClaimHistoryResponse, ClaimKind,
+    ClaimPreferences, ClaimRecipient, ClaimRecord, ClaimShare, CompoundConfig, ConfigResponse,
+    DistributionConfig, ExecuteMsg, InstantiateMsg, LunaValueResponse, NextUnlockResponse,
+    OperatorInfo, OperatorPermissions, OperatorsResponse, QueryMsg, RewardsStrategy,
+    SolvencyResponse, StakingInfo, SuspendedPeriod, SuspensionsResponse, UnlockResponse,
+    UnlockScheduleResponse, VestedAmountResponse, VestingInfoAtResponse, VestingInfoResponse,
+    VestingSchedule,
 };
 
 use cosmwasm_std::{
     from_binary,
     testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR},
-    to_binary, Addr, Attribute, BankMsg, Coin, ContractResult, Reply, Response, StdError, SubMsg,
-    SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
+    to_binary, Addr, Attribute, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal, Deps,
+    Env, Event, Reply, Response, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Denom};
+use cw20::{Cw20ExecuteMsg, Denom, Expiration};
+use protobuf::wire_format::WireType;
 
 #[test]
 fn proper_initialization() {
@@ -26,6 +35,10 @@ fn proper_initialization() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -45,10 +58,19 @@ fn proper_initialization() {
         vec![
             ("action", "create_vesting_account"),
             ("owner_address", "owner0001"),
-            ("vesting_denom", "{\"native\":\"uluna\"}",),
             ("vesting_amount", "1000000"),
         ]
     );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_created").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0001"),
+            ("denom_kind", "native"),
+            ("denom", "uluna"),
+            ("amount", "1000000"),
+        ])]
+    );
     assert_eq!(res.messages.len(), 0);
 }
 
@@ -69,6 +91,10 @@ fn proper_initialization_enable_staking() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -113,6 +139,10 @@ fn invalid_start_time_initialization() {
             end_time: "100".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -126,7 +156,8 @@ fn invalid_start_time_initialization() {
     let mut env = mock_env();
     env.block.time = Timestamp::from_seconds(105);
 
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    let res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidStartTime {});
 }
 
 #[test]
@@ -142,6 +173,10 @@ fn invalid_end_time_initialization() {
             end_time: "100".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -155,7 +190,8 @@ fn invalid_end_time_initialization() {
     let mut env = mock_env();
     env.block.time = Timestamp::from_seconds(100);
 
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    let res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::EndTimeBeforeStartTime {});
 }
 
 #[test]
@@ -171,6 +207,10 @@ fn invalid_initialization_enable_staking_without_staking_info() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -183,8 +223,8 @@ fn invalid_initialization_enable_staking_without_staking_info() {
     let mut env = mock_env();
     env.block.time = Timestamp::from_seconds(100);
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    let res = instantiate(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MissingStakingInfo {});
 }
 
 #[test]
@@ -203,6 +243,10 @@ fn test_reply() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -218,6 +262,21 @@ fn test_reply() {
     // we can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
+    // only the bond reply is expected
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: 99,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::UnknownReplyId { id: 99 });
+
     let msg: Reply = Reply {
         id: 1,
         result: ContractResult::Ok(SubMsgExecutionResponse {
@@ -235,14 +294,23 @@ fn test_reply() {
         vec![
             ("action", "create_vesting_account"),
             ("owner_address", "owner0001"),
-            ("vesting_denom", "{\"cw20\":\"bluna\"}",),
             ("vesting_amount", "999999"),
         ]
     );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_created").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0001"),
+            ("denom_kind", "cw20"),
+            ("denom", "bluna"),
+            ("amount", "999999"),
+        ])]
+    );
 
     assert_eq!(
         from_binary::<VestingInfoResponse>(
-            &query(deps.as_ref(), env, QueryMsg::VestingInfo {}).unwrap()
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap()
         )
         .unwrap(),
         VestingInfoResponse {
@@ -257,8 +325,35 @@ fn test_reply() {
             },
             claimable_amount: Uint128::zero(),
             claimable_staking_rewards: Uint128::zero(),
+            luna_value: Some(LunaValueResponse {
+                exchange_rate: Decimal::one(),
+                bond_exchange_rate: Decimal::one(),
+                vesting_amount: Uint128::new(999999u128),
+                vested_amount: Uint128::zero(),
+                claimable_amount: Uint128::zero(),
+                slashing_loss: Uint128::zero(),
+            }),
+            rewards_error: None,
         }
     );
+
+    // the hub is slashed after bonding
+    deps.querier.with_exchange_rate(Decimal::percent(90));
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap()
+        .luna_value,
+        Some(LunaValueResponse {
+            exchange_rate: Decimal::percent(90),
+            bond_exchange_rate: Decimal::one(),
+            vesting_amount: Uint128::new(899999u128),
+            vested_amount: Uint128::zero(),
+            claimable_amount: Uint128::zero(),
+            slashing_loss: Uint128::new(100000u128),
+        })
+    );
 }
 
 #[test]
@@ -274,6 +369,10 @@ fn test_change_owner() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -292,16 +391,25 @@ fn test_change_owner() {
     // unauthorized
     let msg = ExecuteMsg::ChangeOwner {
         new_owner: "owner0002".to_string(),
+        settle_previous_owner: None,
     };
     let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        ContractError::Unauthorized {} => {}
         _ => panic!("DO NOT ENTER"),
     }
 
     let info = mock_info("owner0001", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("owner_changed").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0002"),
+            ("previous_owner", "owner0001"),
+        ])]
+    );
 
     assert_eq!(
         from_binary::<VestingInfoResponse>(
@@ -320,13 +428,18 @@ fn test_change_owner() {
             },
             claimable_amount: Uint128::zero(),
             claimable_staking_rewards: Uint128::zero(),
+            luna_value: None,
+            rewards_error: None,
         }
     );
 }
 
 #[test]
 fn claim_native() {
-    let mut deps = mock_dependencies_with_querier(&[]);
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
     let msg = InstantiateMsg {
         owner_address: "owner0001".to_string(),
         enable_staking: false,
@@ -336,6 +449,10 @@ fn claim_native() {
             end_time: "110".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
     };
 
     let info = mock_info(
@@ -357,12 +474,14 @@ fn claim_native() {
     // valid claim
     let msg = ExecuteMsg::Claim {
         recipient: Some("addr0001".to_string()),
+        amount: None,
+        recipients: None,
     };
 
     // permission check
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        ContractError::Unauthorized {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -383,12 +502,24 @@ fn claim_native() {
         vec![
             Attribute::new("action", "claim"),
             Attribute::new("recipient", "addr0001"),
-            Attribute::new("vesting_denom", "{\"native\":\"uluna\"}"),
             Attribute::new("vesting_amount", "1000000"),
             Attribute::new("vested_amount", "500000"),
             Attribute::new("claim_amount", "500000"),
+            Attribute::new("claim_index", "0"),
         ],
     );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_claimed").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0001"),
+            ("denom_kind", "native"),
+            ("denom", "uluna"),
+            ("address", "addr0001"),
+            ("amount", "500000"),
+            ("claim_index", "0"),
+        ])]
+    );
 
     // query vesting account
     assert_eq!(
@@ -408,13 +539,19 @@ fn claim_native() {
             },
             claimable_amount: Uint128::zero(),
             claimable_staking_rewards: Uint128::zero(),
+            luna_value: None,
+            rewards_error: None,
         }
     );
 
     // make time to half claimable
     env.block.time = Timestamp::from_seconds(110);
 
-    let msg = ExecuteMsg::Claim { recipient: None };
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(
         res.messages,
@@ -431,10 +568,10 @@ fn claim_native() {
         vec![
             Attribute::new("action", "claim"),
             Attribute::new("recipient", "owner0001"),
-            Attribute::new("vesting_denom", "{\"native\":\"uluna\"}"),
             Attribute::new("vesting_amount", "1000000"),
             Attribute::new("vested_amount", "1000000"),
             Attribute::new("claim_amount", "500000"),
+            Attribute::new("claim_index", "1"),
         ],
     );
 
@@ -456,27 +593,31 @@ fn claim_native() {
             },
             claimable_amount: Uint128::zero(),
             claimable_staking_rewards: Uint128::zero(),
+            luna_value: None,
+            rewards_error: None,
         }
     );
 }
 
 #[test]
-fn claim_cw20() {
-    let mut deps = mock_dependencies_with_querier(&[]);
-
+fn deferred_start() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
     let msg = InstantiateMsg {
         owner_address: "owner0001".to_string(),
-        enable_staking: true,
-        staking_info: Some(StakingInfo {
-            bluna_token: "bluna".to_string(),
-            hub_contract: "hub".to_string(),
-            reward_contract: "reward".to_string(),
-        }),
+        enable_staking: false,
+        staking_info: None,
         vesting_schedule: VestingSchedule {
-            start_time: "105".to_string(),
-            end_time: "110".to_string(),
+            start_time: "0".to_string(),
+            end_time: "10".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: Some("trigger0000".to_string()),
+        admin: None,
     };
 
     let info = mock_info(
@@ -489,139 +630,122 @@ fn claim_cw20() {
     let mut env = mock_env();
     env.block.time = Timestamp::from_seconds(100);
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    let msg: Reply = Reply {
-        id: 1,
-        result: ContractResult::Ok(SubMsgExecutionResponse {
-            events: vec![],
-            data: None,
-        }),
-    };
-
-    deps.querier
-        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
-    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
-    // make time to half claimable
-    env.block.time = Timestamp::from_seconds(105);
+    // the relative schedule is accepted even though it is in the past
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    // valid claim
-    let msg = ExecuteMsg::Claim {
-        recipient: Some("addr0001".to_string()),
+    // nothing vests before the start
+    env.block.time = Timestamp::from_seconds(200);
+    let owner_info = mock_info("owner0001", &[]);
+    let claim_msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
     };
-
-    // permission check
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        claim_msg.clone(),
+    )
+    .unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        ContractError::NothingToClaim {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
-
-    let info = mock_info("owner0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(WasmMsg::Execute {
-            contract_addr: "bluna".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0001".to_string(),
-                amount: Uint128::new(499999u128),
-            })
-            .unwrap(),
-        }),]
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse { unlocks: vec![] }
     );
     assert_eq!(
-        res.attributes,
-        vec![
-            Attribute::new("action", "claim"),
-            Attribute::new("recipient", "addr0001"),
-            Attribute::new("vesting_denom", "{\"cw20\":\"bluna\"}"),
-            Attribute::new("vesting_amount", "999999"),
-            Attribute::new("vested_amount", "499999"),
-            Attribute::new("claim_amount", "499999"),
-        ],
+        from_binary::<ConfigResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()
+        )
+        .unwrap()
+        .start_trigger,
+        Some("trigger0000".to_string())
     );
 
-    // query vesting account
+    // only the trigger can start
+    let msg = ExecuteMsg::StartVesting {};
+    let res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let trigger_info = mock_info("trigger0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        trigger_info.clone(),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        from_binary::<VestingInfoResponse>(
-            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {},).unwrap()
-        )
-        .unwrap(),
-        VestingInfoResponse {
-            owner_address: "owner0001".to_string(),
-            vesting_denom: Denom::Cw20(Addr::unchecked("bluna")),
-            vesting_amount: Uint128::new(999999),
-            vested_amount: Uint128::new(499999),
-            vesting_schedule: VestingSchedule {
-                start_time: "105".to_string(),
-                end_time: "110".to_string(),
-                vesting_interval: "5".to_string(),
-            },
-            claimable_amount: Uint128::zero(),
-            claimable_staking_rewards: Uint128::zero(),
-        }
+        res.attributes,
+        vec![
+            Attribute::new("action", "start_vesting"),
+            Attribute::new("start_time", "200"),
+            Attribute::new("end_time", "210"),
+        ]
     );
 
-    // make time to half claimable
-    env.block.time = Timestamp::from_seconds(110);
+    let res = execute(deps.as_mut(), env.clone(), trigger_info, msg).unwrap_err();
+    match res {
+        ContractError::VestingAlreadyStarted {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let msg = ExecuteMsg::Claim { recipient: None };
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    // the schedule now runs from the start
+    let res = execute(deps.as_mut(), env.clone(), owner_info, claim_msg).unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(WasmMsg::Execute {
-            contract_addr: "bluna".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "owner0001".to_string(),
-                amount: Uint128::new(500000u128),
-            })
-            .unwrap(),
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(333333u128),
+            }],
         }),]
     );
-    assert_eq!(
-        res.attributes,
-        vec![
-            Attribute::new("action", "claim"),
-            Attribute::new("recipient", "owner0001"),
-            Attribute::new("vesting_denom", "{\"cw20\":\"bluna\"}"),
-            Attribute::new("vesting_amount", "999999"),
-            Attribute::new("vested_amount", "999999"),
-            Attribute::new("claim_amount", "500000"),
-        ],
-    );
 
-    // query vesting account
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        from_binary::<VestingInfoResponse>(
-            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
-        )
-        .unwrap(),
-        VestingInfoResponse {
-            owner_address: "owner0001".to_string(),
-            vesting_denom: Denom::Cw20(Addr::unchecked("bluna".to_string())),
-            vesting_amount: Uint128::new(999999),
-            vested_amount: Uint128::new(999999),
-            vesting_schedule: VestingSchedule {
-                start_time: "105".to_string(),
-                end_time: "110".to_string(),
-                vesting_interval: "5".to_string(),
-            },
-            claimable_amount: Uint128::zero(),
-            claimable_staking_rewards: Uint128::zero(),
+        vesting_info.vesting_schedule,
+        VestingSchedule {
+            start_time: "200".to_string(),
+            end_time: "210".to_string(),
+            vesting_interval: "5".to_string(),
         }
     );
+    assert_eq!(
+        from_binary::<ConfigResponse>(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap())
+            .unwrap()
+            .start_trigger,
+        None
+    );
 }
 
 #[test]
-fn claim_rewards() {
+fn deferred_start_compounding() {
     let mut deps = mock_dependencies_with_querier(&[Coin {
         denom: "uusd".to_string(),
-        amount: Uint128::new(300u128),
+        amount: Uint128::new(500u128),
     }]);
-
     let msg = InstantiateMsg {
         owner_address: "owner0001".to_string(),
         enable_staking: true,
@@ -631,10 +755,14 @@ fn claim_rewards() {
             reward_contract: "reward".to_string(),
         }),
         vesting_schedule: VestingSchedule {
-            start_time: "105".to_string(),
-            end_time: "110".to_string(),
+            start_time: "0".to_string(),
+            end_time: "10".to_string(),
             vesting_interval: "5".to_string(),
         },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: Some("trigger0000".to_string()),
+        admin: None,
     };
 
     let info = mock_info(
@@ -646,9 +774,12 @@ fn claim_rewards() {
     );
     let mut env = mock_env();
     env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000000))]);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
     let msg: Reply = Reply {
         id: 1,
         result: ContractResult::Ok(SubMsgExecutionResponse {
@@ -656,74 +787,4302 @@ fn claim_rewards() {
             data: None,
         }),
     };
-
-    deps.querier
-        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
     let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
-    // make time to half claimable
-    env.block.time = Timestamp::from_seconds(105);
 
-    // register rewards
-    deps.querier
-        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+    // 490 bLUNA compounded before the start, vested immediately once started
+    let owner_info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::SetCompounding {
+        compounding: Some(CompoundConfig {
+            pair_contract: None,
+            follow_schedule: false,
+        }),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::Compound {
+        minimum_receive: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg).unwrap();
 
-    // query vesting account
-    assert_eq!(
-        from_binary::<VestingInfoResponse>(
-            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {},).unwrap()
-        )
-        .unwrap(),
-        VestingInfoResponse {
-            owner_address: "owner0001".to_string(),
-            vesting_denom: Denom::Cw20(Addr::unchecked("bluna")),
-            vesting_amount: Uint128::new(999999),
-            vested_amount: Uint128::new(499999),
-            vesting_schedule: VestingSchedule {
-                start_time: "105".to_string(),
-                end_time: "110".to_string(),
-                vesting_interval: "5".to_string(),
-            },
-            claimable_amount: Uint128::new(499999),
-            claimable_staking_rewards: Uint128::new(300 + 500),
-        }
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(490u128),
+        }],
     );
+    for id in [2, 3] {
+        if id == 3 {
+            deps.querier.with_native_balance(MOCK_CONTRACT_ADDR, &[]);
+            deps.querier.with_balances_querier(&[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::new(1000490),
+            )]);
+        }
 
-    // valid claim
-    let msg = ExecuteMsg::ClaimRewards {
-        recipient: Some("addr0001".to_string()),
-    };
+        let msg: Reply = Reply {
+            id,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    }
 
-    // permission check
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    // the compounded amount is not claimable before the start either
+    let claim_msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        claim_msg.clone(),
+    )
+    .unwrap_err();
     match res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        ContractError::NothingToClaim {} => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info("owner0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info.vesting_amount, Uint128::new(1000490));
+    assert_eq!(vesting_info.vested_amount, Uint128::zero());
+    assert_eq!(vesting_info.claimable_amount, Uint128::zero());
     assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(BankMsg::Send {
-                to_address: "addr0001".to_string(),
-                amount: vec![Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::new(300u128),
-                }],
-            }),
-            SubMsg::new(WasmMsg::Execute {
-                contract_addr: "reward".to_string(),
-                funds: vec![],
-                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards {
-                    recipient: Some("addr0001".to_string()),
-                })
-                .unwrap(),
-            }),
-        ]
+        from_binary::<VestedAmountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestedAmount { time: 300 }
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .vested_amount,
+        Uint128::zero()
     );
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("trigger0000", &[]),
+        ExecuteMsg::StartVesting {},
+    )
+    .unwrap();
+
+    // the first unlock comes with the compounded amount
     assert_eq!(
-        res.attributes,
-        vec![("action", "claim_rewards"), ("rewards_claim_amount", "800")],
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse {
+            unlocks: vec![UnlockResponse {
+                time: 100,
+                amount: Uint128::new(333333),
+                cumulative_amount: Uint128::new(333823),
+            }],
+        }
+    );
+
+    let res = execute(deps.as_mut(), env, owner_info, claim_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner0001".to_string(),
+                amount: Uint128::new(333823u128),
+            })
+            .unwrap(),
+        })]
     );
 }
+
+#[test]
+fn suspend_and_resume() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "120".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: Some("admin0000".to_string()),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // only the funder or the admin can suspend
+    env.block.time = Timestamp::from_seconds(106);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::Suspend {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let admin_info = mock_info("admin0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        admin_info.clone(),
+        ExecuteMsg::Suspend {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "suspend"),
+            Attribute::new("suspended_at", "106"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        admin_info,
+        ExecuteMsg::Suspend {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::AlreadySuspended {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // vesting stops advancing and the end time moves with the block time
+    env.block.time = Timestamp::from_seconds(120);
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info.vested_amount, Uint128::new(250000));
+    assert_eq!(
+        vesting_info.vesting_schedule,
+        VestingSchedule {
+            start_time: "119".to_string(),
+            end_time: "134".to_string(),
+            vesting_interval: "5".to_string(),
+        }
+    );
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse { unlock: None }
+    );
+
+    // the funder can resume
+    env.block.time = Timestamp::from_seconds(126);
+    let funder_info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        funder_info.clone(),
+        ExecuteMsg::Resume {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "resume"),
+            Attribute::new("resumed_at", "126"),
+            Attribute::new("suspended_duration", "20"),
+            Attribute::new("end_time", "140"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        funder_info,
+        ExecuteMsg::Resume {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::NotSuspended {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    assert_eq!(
+        from_binary::<SuspensionsResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::Suspensions {}).unwrap()
+        )
+        .unwrap(),
+        SuspensionsResponse {
+            suspended_periods: vec![SuspendedPeriod {
+                start_time: 106,
+                end_time: Some(126),
+            }],
+            suspended_duration: 20,
+        }
+    );
+
+    // the rest of the schedule is pushed back by the suspended duration
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse {
+            unlock: Some(UnlockResponse {
+                time: 130,
+                amount: Uint128::new(250000),
+                cumulative_amount: Uint128::new(500000),
+            }),
+        }
+    );
+    assert_eq!(
+        from_binary::<VestedAmountResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VestedAmount { time: 139 }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        VestedAmountResponse {
+            time: 139,
+            vested_amount: Uint128::new(750000),
+        }
+    );
+    assert_eq!(
+        from_binary::<VestedAmountResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestedAmount { time: 140 }).unwrap()
+        )
+        .unwrap(),
+        VestedAmountResponse {
+            time: 140,
+            vested_amount: Uint128::new(1000000),
+        }
+    );
+}
+
+#[test]
+fn unlock_schedule_after_resume() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "100".to_string(),
+            end_time: "190".to_string(),
+            vesting_interval: "10".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(90);
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let unlock = |time: u64, cumulative_amount: u128| UnlockResponse {
+        time,
+        amount: Uint128::new(100000),
+        cumulative_amount: Uint128::new(cumulative_amount),
+    };
+    let unlock_schedule = |deps: Deps, env: Env| {
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::UnlockSchedule {
+                    start_after: None,
+                    limit: Some(30),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .unlocks
+    };
+
+    env.block.time = Timestamp::from_seconds(135);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Suspend {},
+    )
+    .unwrap();
+
+    // unlocks before the suspension stay, the later ones are unknown
+    env.block.time = Timestamp::from_seconds(140);
+    assert_eq!(
+        unlock_schedule(deps.as_ref(), env.clone()),
+        vec![
+            unlock(100, 100000),
+            unlock(110, 200000),
+            unlock(120, 300000),
+            unlock(130, 400000),
+        ]
+    );
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse { unlock: None }
+    );
+
+    env.block.time = Timestamp::from_seconds(155);
+    let _res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Resume {}).unwrap();
+
+    // only the unlocks after the suspension move, by its duration
+    assert_eq!(
+        unlock_schedule(deps.as_ref(), env.clone()),
+        vec![
+            unlock(100, 100000),
+            unlock(110, 200000),
+            unlock(120, 300000),
+            unlock(130, 400000),
+            unlock(160, 500000),
+            unlock(170, 600000),
+            unlock(180, 700000),
+            unlock(190, 800000),
+            unlock(200, 900000),
+            unlock(210, 1000000),
+        ]
+    );
+}
+
+#[test]
+fn claim_native_partial() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // cannot claim more than claimable amount
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: Some(Uint128::new(500001u128)),
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    match res {
+        ContractError::ClaimAmountExceeded {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // zero amount claim
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: Some(Uint128::zero()),
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    match res {
+        ContractError::ZeroClaimAmount {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: Some(Uint128::new(200000u128)),
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(200000u128),
+            }],
+        }),]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim"),
+            Attribute::new("recipient", "owner0001"),
+            Attribute::new("vesting_amount", "1000000"),
+            Attribute::new("vested_amount", "500000"),
+            Attribute::new("claim_amount", "200000"),
+            Attribute::new("claim_index", "0"),
+        ],
+    );
+
+    // the remainder stays claimable
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Native("uluna".to_string()),
+            vesting_amount: Uint128::new(1000000),
+            vested_amount: Uint128::new(500000),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+            claimable_amount: Uint128::new(300000),
+            claimable_staking_rewards: Uint128::zero(),
+            luna_value: None,
+            rewards_error: None,
+        }
+    );
+
+    // claim the rest after full vesting
+    env.block.time = Timestamp::from_seconds(110);
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(800000u128),
+            }],
+        }),]
+    );
+}
+
+#[test]
+fn vesting_info_at() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    env.block.time = Timestamp::from_seconds(105);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner0001", &[]), msg).unwrap();
+
+    let vesting_info_at = |time: u64| {
+        from_binary::<VestingInfoAtResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfoAt { time }).unwrap(),
+        )
+        .unwrap()
+    };
+
+    // past
+    assert_eq!(
+        vesting_info_at(100),
+        VestingInfoAtResponse {
+            time: 100,
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Native("uluna".to_string()),
+            vesting_amount: Uint128::new(1000000),
+            vested_amount: Uint128::zero(),
+            claimed_amount: Uint128::new(500000),
+            claimable_amount: Uint128::zero(),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+        }
+    );
+
+    // future
+    let response = vesting_info_at(110);
+    assert_eq!(response.vested_amount, Uint128::new(1000000));
+    assert_eq!(response.claimable_amount, Uint128::new(500000));
+
+    assert_eq!(
+        from_binary::<VestedAmountResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestedAmount { time: 107 }).unwrap()
+        )
+        .unwrap(),
+        VestedAmountResponse {
+            time: 107,
+            vested_amount: Uint128::new(500000),
+        }
+    );
+}
+
+#[test]
+fn unlock_schedule() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "115".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let unlock = |time: u64, amount: u128, cumulative_amount: u128| UnlockResponse {
+        time,
+        amount: Uint128::new(amount),
+        cumulative_amount: Uint128::new(cumulative_amount),
+    };
+
+    assert_eq!(
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: None,
+                    limit: None,
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse {
+            unlocks: vec![
+                unlock(105, 333333, 333333),
+                unlock(110, 333333, 666666),
+                unlock(115, 333334, 1000000),
+            ],
+        }
+    );
+
+    assert_eq!(
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: Some(105),
+                    limit: Some(1),
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse {
+            unlocks: vec![unlock(110, 333333, 666666)],
+        }
+    );
+
+    // nothing after the end, however far
+    assert_eq!(
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: Some(u64::MAX - 3),
+                    limit: None,
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse { unlocks: vec![] }
+    );
+
+    env.block.time = Timestamp::from_seconds(112);
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse {
+            unlock: Some(unlock(115, 333334, 1000000)),
+        }
+    );
+
+    env.block.time = Timestamp::from_seconds(115);
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env, QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse { unlock: None }
+    );
+}
+
+#[test]
+fn claim_cw20() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // valid claim
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("addr0001".to_string()),
+        amount: None,
+        recipients: None,
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::new(499999u128),
+            })
+            .unwrap(),
+        }),]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim"),
+            Attribute::new("recipient", "addr0001"),
+            Attribute::new("vesting_amount", "999999"),
+            Attribute::new("vested_amount", "499999"),
+            Attribute::new("claim_amount", "499999"),
+            Attribute::new("claim_index", "0"),
+        ],
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("vesting_claimed").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0001"),
+            ("denom_kind", "cw20"),
+            ("denom", "bluna"),
+            ("address", "addr0001"),
+            ("amount", "499999"),
+            ("claim_index", "0"),
+        ])]
+    );
+
+    // query vesting account
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Cw20(Addr::unchecked("bluna")),
+            vesting_amount: Uint128::new(999999),
+            vested_amount: Uint128::new(499999),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+            claimable_amount: Uint128::zero(),
+            claimable_staking_rewards: Uint128::zero(),
+            luna_value: Some(LunaValueResponse {
+                exchange_rate: Decimal::one(),
+                bond_exchange_rate: Decimal::one(),
+                vesting_amount: Uint128::new(999999),
+                vested_amount: Uint128::new(499999),
+                claimable_amount: Uint128::zero(),
+                slashing_loss: Uint128::zero(),
+            }),
+            rewards_error: None,
+        }
+    );
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(110);
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner0001".to_string(),
+                amount: Uint128::new(500000u128),
+            })
+            .unwrap(),
+        }),]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim"),
+            Attribute::new("recipient", "owner0001"),
+            Attribute::new("vesting_amount", "999999"),
+            Attribute::new("vested_amount", "999999"),
+            Attribute::new("claim_amount", "500000"),
+            Attribute::new("claim_index", "1"),
+        ],
+    );
+
+    // query vesting account
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Cw20(Addr::unchecked("bluna".to_string())),
+            vesting_amount: Uint128::new(999999),
+            vested_amount: Uint128::new(999999),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+            claimable_amount: Uint128::zero(),
+            claimable_staking_rewards: Uint128::zero(),
+            luna_value: Some(LunaValueResponse {
+                exchange_rate: Decimal::one(),
+                bond_exchange_rate: Decimal::one(),
+                vesting_amount: Uint128::new(999999),
+                vested_amount: Uint128::new(999999),
+                claimable_amount: Uint128::zero(),
+                slashing_loss: Uint128::zero(),
+            }),
+            rewards_error: None,
+        }
+    );
+}
+
+#[test]
+fn principal_deficit_and_surplus() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(800000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: Some("admin0000".to_string()),
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    assert_eq!(
+        from_binary::<SolvencyResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::Solvency {}).unwrap()
+        )
+        .unwrap(),
+        SolvencyResponse {
+            vesting_denom: Denom::Native("uluna".to_string()),
+            balance: Uint128::new(800000),
+            obligation: Uint128::new(1000000),
+            surplus: Uint128::zero(),
+            deficit: Uint128::new(200000),
+        }
+    );
+
+    // a deficit is not reconciled
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::NoSurplus {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // queries report what claims pay out
+    assert_eq!(
+        from_binary::<UnlockScheduleResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::UnlockSchedule {
+                    start_after: None,
+                    limit: None,
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        UnlockScheduleResponse {
+            unlocks: vec![
+                UnlockResponse {
+                    time: 105,
+                    amount: Uint128::new(400000),
+                    cumulative_amount: Uint128::new(500000),
+                },
+                UnlockResponse {
+                    time: 110,
+                    amount: Uint128::new(400000),
+                    cumulative_amount: Uint128::new(1000000),
+                },
+            ],
+        }
+    );
+    let vesting_info_at = from_binary::<VestingInfoAtResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VestingInfoAt { time: 105 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info_at.vested_amount, Uint128::new(500000));
+    assert_eq!(vesting_info_at.claimable_amount, Uint128::new(400000));
+
+    env.block.time = Timestamp::from_seconds(105);
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info.vested_amount, Uint128::new(500000));
+    assert_eq!(vesting_info.claimable_amount, Uint128::new(400000));
+    assert_eq!(
+        from_binary::<NextUnlockResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::NextUnlock {}).unwrap()
+        )
+        .unwrap(),
+        NextUnlockResponse {
+            unlock: Some(UnlockResponse {
+                time: 110,
+                amount: Uint128::new(400000),
+                cumulative_amount: Uint128::new(1000000),
+            }),
+        }
+    );
+
+    // claims are paid out pro rata, fixed amounts included
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: Some(vec![
+            ClaimRecipient {
+                address: "wallet0000".to_string(),
+                share: ClaimShare::Amount(Uint128::new(200000u128)),
+            },
+            ClaimRecipient {
+                address: "owner0001".to_string(),
+                share: ClaimShare::Ratio(Decimal::one()),
+            },
+        ]),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "wallet0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(160000u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(240000u128),
+                }],
+            })
+        ]
+    );
+
+    // 300000 more than owed, e.g. sent to the contract by mistake
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(700000u128),
+        }],
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the admin can reconcile along with the owner
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin0000", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "reconcile"),
+            Attribute::new("balance", "700000"),
+            Attribute::new("surplus", "200000"),
+            Attribute::new("vesting_amount", "1200000"),
+        ]
+    );
+
+    // the surplus follows the schedule
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info.vested_amount, Uint128::new(600000));
+    assert_eq!(vesting_info.claimable_amount, Uint128::new(100000));
+
+    assert_eq!(
+        from_binary::<SolvencyResponse>(&query(deps.as_ref(), env, QueryMsg::Solvency {}).unwrap())
+            .unwrap(),
+        SolvencyResponse {
+            vesting_denom: Denom::Native("uluna".to_string()),
+            balance: Uint128::new(700000),
+            obligation: Uint128::new(700000),
+            surplus: Uint128::zero(),
+            deficit: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn sweep() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Sweep {
+        asset: Denom::Native("uluna".to_string()),
+        recipient: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // principal can not be swept
+    let funder = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), funder.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::NothingToSweep {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(1200000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(5000u128),
+            },
+        ],
+    );
+    deps.querier
+        .with_token_balance("token0000", MOCK_CONTRACT_ADDR, Uint128::new(100u128));
+
+    let res = execute(deps.as_mut(), env.clone(), funder, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(200000u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "sweep"),
+            Attribute::new("recipient", "addr0000"),
+            Attribute::new("asset_kind", "native"),
+            Attribute::new("asset", "uluna"),
+            Attribute::new("sweep_amount", "200000"),
+        ]
+    );
+
+    // without staking, uusd is not owed to anyone
+    let owner = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::Sweep {
+        asset: Denom::Native("uusd".to_string()),
+        recipient: Some("addr0002".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0002".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(5000u128),
+            }],
+        })]
+    );
+
+    let msg = ExecuteMsg::Sweep {
+        asset: Denom::Cw20(Addr::unchecked("token0000")),
+        recipient: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner0001".to_string(),
+                amount: Uint128::new(100u128),
+            })
+            .unwrap(),
+        })]
+    );
+
+    // recipients are restricted by the allowlist, except the funder
+    let msg = ExecuteMsg::SetClaimPreferences {
+        principal_recipient: None,
+        rewards_recipient: None,
+        allowed_recipients: Some(vec!["addr0003".to_string()]),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Sweep {
+        asset: Denom::Native("uusd".to_string()),
+        recipient: Some("addr0002".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner, msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::RecipientNotAllowed {
+            recipient: "addr0002".to_string()
+        }
+    );
+
+    let msg = ExecuteMsg::Sweep {
+        asset: Denom::Native("uluna".to_string()),
+        recipient: None,
+    };
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(200000u128),
+            }],
+        })]
+    );
+}
+
+#[test]
+fn vesting_info_with_failing_reward_contract() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000000))]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    deps.querier.with_failing_contract("reward");
+    env.block.time = Timestamp::from_seconds(105);
+
+    // principal is still reported
+    let vesting_info = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(vesting_info.vested_amount, Uint128::new(500000));
+    assert_eq!(vesting_info.claimable_amount, Uint128::new(500000));
+    assert_eq!(vesting_info.claimable_staking_rewards, Uint128::zero());
+    assert_eq!(vesting_info.luna_value, None);
+    assert!(vesting_info
+        .rewards_error
+        .unwrap()
+        .contains("reward is paused"));
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Rewards {}).unwrap_err();
+    assert!(res.to_string().contains("reward is paused"));
+
+    assert_eq!(
+        from_binary::<ConfigResponse>(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap())
+            .unwrap(),
+        ConfigResponse {
+            owner_address: "owner0001".to_string(),
+            funder: "addr0000".to_string(),
+            staking_enabled: true,
+            staking_info: Some(StakingInfo {
+                bluna_token: "bluna".to_string(),
+                hub_contract: "hub".to_string(),
+                reward_contract: "reward".to_string(),
+            }),
+            claim_preferences: ClaimPreferences::default(),
+            distribution: DistributionConfig::default(),
+            compounding: None,
+            rewards_strategy: RewardsStrategy::Payout,
+            start_trigger: None,
+            admin: None,
+        }
+    );
+}
+
+#[test]
+fn claim_with_failing_reward_contract() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    let owner = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::SetDistribution {
+        distribute_rewards: true,
+        keeper_bounty: Decimal::percent(10),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+    // 100 rewards accrue while the reward contract cannot be queried
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100u128))]);
+    deps.querier.with_failing_contract("reward");
+
+    let principal_transfer = SubMsg::new(WasmMsg::Execute {
+        contract_addr: "bluna".to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: "owner0001".to_string(),
+            amount: Uint128::new(500u128),
+        })
+        .unwrap(),
+    });
+
+    env.block.time = Timestamp::from_seconds(105);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+    assert_eq!(res.messages, vec![principal_transfer.clone()]);
+
+    // rewards are not distributed, the principal is
+    env.block.time = Timestamp::from_seconds(110);
+    let keeper = mock_info("keeper0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper,
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![principal_transfer]);
+
+    // the rewards are settled to the owner once the reward contract is back
+    deps.querier.with_recovered_contract("reward");
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap()
+        .claimable_staking_rewards,
+        Uint128::new(100u128),
+    );
+
+    deps.querier.with_failing_contract("reward");
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+        settle_previous_owner: None,
+    };
+    let res = execute(deps.as_mut(), env, owner, msg).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn claim_rewards() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(300u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // register rewards
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+
+    // query vesting account
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Cw20(Addr::unchecked("bluna")),
+            vesting_amount: Uint128::new(999999),
+            vested_amount: Uint128::new(499999),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+            claimable_amount: Uint128::new(499999),
+            // (300 + 500) / 999999 per bLUNA, rounded down at the reward index
+            claimable_staking_rewards: Uint128::new(799),
+            luna_value: Some(LunaValueResponse {
+                exchange_rate: Decimal::one(),
+                bond_exchange_rate: Decimal::one(),
+                vesting_amount: Uint128::new(999999),
+                vested_amount: Uint128::new(499999),
+                claimable_amount: Uint128::new(499999),
+                slashing_loss: Uint128::zero(),
+            }),
+            rewards_error: None,
+        }
+    );
+
+    // valid claim
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: Some("addr0001".to_string()),
+        recipients: None,
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(799u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("rewards_claim_amount", "799"),
+            ("claim_index", "0"),
+        ],
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("rewards_claimed").add_attributes(vec![
+            ("contract", MOCK_CONTRACT_ADDR),
+            ("owner", "owner0001"),
+            ("denom_kind", "native"),
+            ("denom", "uusd"),
+            ("address", "addr0001"),
+            ("amount", "799"),
+            ("claim_index", "0"),
+        ])]
+    );
+}
+
+#[test]
+fn compound_rewards() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(500u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000000))]);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    env.block.time = Timestamp::from_seconds(105);
+
+    // compounding is not enabled yet
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Compound {
+            minimum_receive: None,
+        },
+    );
+    match res {
+        Err(ContractError::CompoundingDisabled {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SetCompounding {
+        compounding: Some(CompoundConfig {
+            pair_contract: None,
+            follow_schedule: false,
+        }),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // native market swap
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::Compound {
+            minimum_receive: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].id, 2);
+    match &res.messages[0].msg {
+        CosmosMsg::Stargate { type_url, value } => {
+            assert_eq!(type_url, "/terra.market.v1beta1.MsgSwap");
+            assert_eq!(
+                decode_msg_swap(value),
+                (
+                    MOCK_CONTRACT_ADDR.to_string(),
+                    ("uusd".to_string(), "500".to_string()),
+                    "uluna".to_string()
+                )
+            );
+
+            // terra/market/v1beta1/tx.proto: trader = 1, offer_coin = 2, ask_denom = 3,
+            // and cosmos/base/v1beta1/coin.proto: denom = 1, amount = 2
+            let expected: Vec<u8> = [
+                &[0x0a, 0x0f][..],
+                MOCK_CONTRACT_ADDR.as_bytes(),
+                &[0x12, 0x0b, 0x0a, 0x04],
+                b"uusd",
+                &[0x12, 0x03],
+                b"500",
+                &[0x1a, 0x05],
+                b"uluna",
+            ]
+            .concat();
+            assert_eq!(value.as_slice(), expected.as_slice());
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the swapped rewards are paid, so the next compound
+    // goes through the pair contract with new rewards.
+    // uluna sent by mistake is not bonded along with the swapped uluna.
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(1000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100u128),
+            },
+        ],
+    );
+    let msg = ExecuteMsg::SetCompounding {
+        compounding: Some(CompoundConfig {
+            pair_contract: Some("pair".to_string()),
+            follow_schedule: false,
+        }),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::Compound {
+        minimum_receive: Some(Uint128::new(990u128)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: "pair".to_string(),
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: Uint128::new(1000u128),
+                    },
+                    belief_price: Some(Decimal::from_ratio(1000u128, 990u128)),
+                    max_spread: Some(Decimal::permille(10)),
+                    to: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(1000u128),
+                }],
+            },
+            2,
+        )]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![("action", "compound"), ("compound_amount", "1000")]
+    );
+
+    // the swap returned less than minimum_receive
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1089u128),
+        }],
+    );
+    let msg: Reply = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), env.clone(), msg.clone());
+    assert_eq!(
+        res,
+        Err(ContractError::MinimumReceiveNotMet {
+            amount: Uint128::new(989u128),
+            minimum_receive: Uint128::new(990u128),
+        })
+    );
+
+    // bond only the swapped uluna
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1090u128),
+        }],
+    );
+    let res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: to_binary(&HubContractExecuteMsg::Bond {}).unwrap(),
+                funds: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(990u128),
+                }],
+            },
+            3,
+        )]
+    );
+
+    // bonded bLUNA is vested immediately
+    deps.querier.with_native_balance(MOCK_CONTRACT_ADDR, &[]);
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000980))]);
+    let msg: Reply = Reply {
+        id: 3,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![("compounded_amount", "980"), ("follow_schedule", "false")]
+    );
+
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap(),
+        VestingInfoResponse {
+            owner_address: "owner0001".to_string(),
+            vesting_denom: Denom::Cw20(Addr::unchecked("bluna")),
+            vesting_amount: Uint128::new(1000980),
+            vested_amount: Uint128::new(500980),
+            vesting_schedule: VestingSchedule {
+                start_time: "105".to_string(),
+                end_time: "110".to_string(),
+                vesting_interval: "5".to_string(),
+            },
+            claimable_amount: Uint128::new(500980),
+            claimable_staking_rewards: Uint128::zero(),
+            luna_value: Some(LunaValueResponse {
+                exchange_rate: Decimal::one(),
+                bond_exchange_rate: Decimal::one(),
+                vesting_amount: Uint128::new(1000980),
+                vested_amount: Uint128::new(500980),
+                claimable_amount: Uint128::new(500980),
+                slashing_loss: Uint128::zero(),
+            }),
+            rewards_error: None,
+        }
+    );
+}
+
+#[test]
+fn rewards_strategy() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(300u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000000))]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    env.block.time = Timestamp::from_seconds(105);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+
+    // hold rewards in the contract
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::SetRewardsStrategy {
+        rewards_strategy: RewardsStrategy::Hold,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: Some("addr0001".to_string()),
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::RecipientNotSupported {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "reward".to_string(),
+            funds: vec![],
+            msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None }).unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("rewards_position_amount", "800")
+        ]
+    );
+
+    // the held rewards are not counted as new rewards
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(800u128),
+        }],
+    );
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap()
+        .claimable_staking_rewards,
+        Uint128::zero()
+    );
+
+    let deposit_to_market = RewardsStrategy::DepositToMarket {
+        market_contract: "market".to_string(),
+        aterra_token: "aust".to_string(),
+    };
+    let msg = ExecuteMsg::SetRewardsStrategy {
+        rewards_strategy: deposit_to_market.clone(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::RewardsPositionOpen {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ChangeOwner {
+            new_owner: "owner0002".to_string(),
+            settle_previous_owner: None,
+        },
+    );
+    match res {
+        Err(ContractError::RewardsPositionOpen {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::WithdrawRewardsPosition { recipient: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(800u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "withdraw_rewards_position"),
+            ("recipient", "owner0001"),
+            ("withdraw_amount", "800"),
+            ("claim_index", "0"),
+        ]
+    );
+
+    // deposit new rewards to the money market
+    deps.querier.with_native_balance(MOCK_CONTRACT_ADDR, &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(1000u128),
+        }],
+    );
+
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(1000u128),
+            }],
+            msg: to_binary(&MarketExecuteMsg::DepositStable {}).unwrap(),
+        })]
+    );
+
+    deps.querier
+        .with_token_balance("aust", MOCK_CONTRACT_ADDR, Uint128::new(950u128));
+    let msg = ExecuteMsg::WithdrawRewardsPosition {
+        recipient: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "aust".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::new(950u128),
+            })
+            .unwrap(),
+        })]
+    );
+}
+
+#[test]
+fn claim_rewards_with_tax() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(800u128),
+    }]);
+    deps.querier.with_tax(
+        Decimal::permille(5),
+        &[(&"uusd".to_string(), &Uint128::new(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000000))]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    env.block.time = Timestamp::from_seconds(105);
+
+    // the whole balance is claimed, the tax is paid out of it
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env, mock_info("owner0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                // 800 / 1.005
+                amount: Uint128::new(796u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("rewards_claim_amount", "800"),
+            ("claim_index", "0"),
+        ],
+    );
+}
+
+#[test]
+fn claim_rewards_after_change_owner() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    // 100 rewards on 1000 bLUNA, then half of the principal is claimed
+    env.block.time = Timestamp::from_seconds(105);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100u128))]);
+
+    let owner = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+    // 150 more rewards on the remaining 500 bLUNA
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(250u128))]);
+
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+        settle_previous_owner: Some(false),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+
+    // rewards after the transfer belong to the new owner
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(350u128))]);
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap()
+        .claimable_staking_rewards,
+        Uint128::new(100u128),
+    );
+
+    // the previous owner claims rewards settled to it
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(250u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("previous_owner", "owner0001"),
+            ("recipient", "owner0001"),
+            ("rewards_claim_amount", "250"),
+            ("claim_index", "1"),
+        ]
+    );
+
+    let res = execute(deps.as_mut(), env, owner, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn change_owner_resets_reward_settings() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    let owner = mock_info("owner0001", &[]);
+    for msg in [
+        ExecuteMsg::SetDistribution {
+            distribute_rewards: true,
+            keeper_bounty: Decimal::percent(10),
+        },
+        ExecuteMsg::SetCompounding {
+            compounding: Some(CompoundConfig {
+                pair_contract: Some("pair".to_string()),
+                follow_schedule: true,
+            }),
+        },
+        ExecuteMsg::SetRewardsStrategy {
+            rewards_strategy: RewardsStrategy::Hold,
+        },
+    ] {
+        let _res = execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+        settle_previous_owner: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner, msg).unwrap();
+
+    // the new owner opts in again instead of inheriting them
+    let config =
+        from_binary::<ConfigResponse>(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap())
+            .unwrap();
+    assert_eq!(config.owner_address, "owner0002".to_string());
+    assert_eq!(config.distribution, DistributionConfig::default());
+    assert_eq!(config.compounding, None);
+    assert_eq!(config.rewards_strategy, RewardsStrategy::Payout);
+}
+
+#[test]
+fn change_owner_settles_previous_owner() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100u128))]);
+
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+        settle_previous_owner: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("owner0001", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "owner0001".to_string(),
+                    amount: Uint128::new(500u128),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(100u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "change_owner"),
+            ("previous_owner", "owner0001"),
+            ("new_owner", "owner0002"),
+            ("settle_previous_owner", "true"),
+            ("vested_amount", "500"),
+            ("claimed_amount", "500"),
+            ("settled_amount", "500"),
+            ("settled_rewards", "100"),
+            ("pending_rewards", "0"),
+            ("remaining_amount", "500"),
+            ("claim_index", "0"),
+            ("claim_index", "1"),
+        ]
+    );
+
+    // only the future stream moves to the new owner
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::zero(),
+        }],
+    );
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())]);
+    let res = from_binary::<VestingInfoResponse>(
+        &query(deps.as_ref(), env, QueryMsg::VestingInfo {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.owner_address, "owner0002".to_string());
+    assert_eq!(res.claimable_amount, Uint128::zero());
+    assert_eq!(res.claimable_staking_rewards, Uint128::zero());
+}
+
+#[test]
+fn claim_and_send_native() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    let msg = ExecuteMsg::ClaimAndSend {
+        contract: "staking0000".to_string(),
+        msg: Binary::from(b"{\"stake\":{}}".to_vec()),
+        amount: None,
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking0000".to_string(),
+            msg: Binary::from(b"{\"stake\":{}}".to_vec()),
+            funds: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(500000u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim_and_send"),
+            Attribute::new("contract", "staking0000"),
+            Attribute::new("vesting_amount", "1000000"),
+            Attribute::new("vested_amount", "500000"),
+            Attribute::new("claim_amount", "500000"),
+            Attribute::new("claim_index", "0"),
+        ],
+    );
+
+    // query vesting account
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap()
+        .claimable_amount,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn claim_and_send_cw20() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    let msg = ExecuteMsg::ClaimAndSend {
+        contract: "pair0000".to_string(),
+        msg: Binary::from(b"{\"provide\":{}}".to_vec()),
+        amount: Some(Uint128::new(100000u128)),
+    };
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "pair0000".to_string(),
+                amount: Uint128::new(100000u128),
+                msg: Binary::from(b"{\"provide\":{}}".to_vec()),
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim_and_send"),
+            Attribute::new("contract", "pair0000"),
+            Attribute::new("vesting_amount", "999999"),
+            Attribute::new("vested_amount", "499999"),
+            Attribute::new("claim_amount", "100000"),
+            Attribute::new("claim_index", "0"),
+        ],
+    );
+
+    // query vesting account
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap()
+        .claimable_amount,
+        Uint128::new(399999u128),
+    );
+}
+
+#[test]
+fn claim_split_recipients() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    let info = mock_info("owner0001", &[]);
+
+    // recipient and recipients cannot be given together
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("addr0001".to_string()),
+        amount: None,
+        recipients: Some(vec![ClaimRecipient {
+            address: "addr0001".to_string(),
+            share: ClaimShare::Ratio(Decimal::one()),
+        }]),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    match res {
+        ContractError::ConflictingRecipients {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // 500000 is split into 3 parts, remainder goes to the first recipient
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: Some(vec![
+            ClaimRecipient {
+                address: "tax0000".to_string(),
+                share: ClaimShare::Ratio(Decimal::from_ratio(1u64, 3u64)),
+            },
+            ClaimRecipient {
+                address: "addr0001".to_string(),
+                share: ClaimShare::Ratio(Decimal::one() - Decimal::from_ratio(1u64, 3u64)),
+            },
+        ]),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "tax0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(166667u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(333333u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "claim"),
+            Attribute::new("recipient", "tax0000"),
+            Attribute::new("recipient_amount", "166667"),
+            Attribute::new("claim_index", "0"),
+            Attribute::new("recipient", "addr0001"),
+            Attribute::new("recipient_amount", "333333"),
+            Attribute::new("claim_index", "1"),
+            Attribute::new("vesting_amount", "1000000"),
+            Attribute::new("vested_amount", "500000"),
+            Attribute::new("claim_amount", "500000"),
+        ],
+    );
+
+    // make time to fully claimable
+    env.block.time = Timestamp::from_seconds(110);
+
+    // fixed amounts only claim the sum of the amounts
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: Some(vec![
+            ClaimRecipient {
+                address: "tax0000".to_string(),
+                share: ClaimShare::Amount(Uint128::new(100000u128)),
+            },
+            ClaimRecipient {
+                address: "addr0001".to_string(),
+                share: ClaimShare::Amount(Uint128::new(200000u128)),
+            },
+        ]),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "tax0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(100000u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(200000u128),
+                }],
+            }),
+        ]
+    );
+
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap()
+        .claimable_amount,
+        Uint128::new(200000u128),
+    );
+}
+
+#[test]
+fn claim_rewards_split_recipients() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(300u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    env.block.time = Timestamp::from_seconds(105);
+
+    // register rewards
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: Some(vec![
+            ClaimRecipient {
+                address: "tax0000".to_string(),
+                share: ClaimShare::Amount(Uint128::new(101u128)),
+            },
+            ClaimRecipient {
+                address: "addr0001".to_string(),
+                share: ClaimShare::Ratio(Decimal::one()),
+            },
+        ]),
+    };
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "tax0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(101u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(698u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("recipient", "tax0000"),
+            ("recipient_amount", "101"),
+            ("claim_index", "0"),
+            ("recipient", "addr0001"),
+            ("recipient_amount", "698"),
+            ("claim_index", "1"),
+            ("rewards_claim_amount", "799"),
+        ],
+    );
+}
+
+#[test]
+fn claim_preferences() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::SetClaimPreferences {
+        principal_recipient: Some("wallet0000".to_string()),
+        rewards_recipient: None,
+        allowed_recipients: Some(vec!["wallet0000".to_string(), "pair0000".to_string()]),
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // default recipient must be allowed
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::SetClaimPreferences {
+            principal_recipient: Some("wallet0001".to_string()),
+            rewards_recipient: None,
+            allowed_recipients: Some(vec!["wallet0000".to_string()]),
+        },
+    )
+    .unwrap_err();
+    match res {
+        ContractError::RecipientNotAllowed { recipient } => {
+            assert_eq!(recipient, "wallet0001")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.attributes, vec![("action", "set_claim_preferences")]);
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // not allowed recipient
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("addr0003".to_string()),
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    match res {
+        ContractError::RecipientNotAllowed { recipient } => {
+            assert_eq!(recipient, "addr0003")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::ClaimAndSend {
+        contract: "addr0003".to_string(),
+        msg: Binary::default(),
+        amount: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    match res {
+        ContractError::RecipientNotAllowed { recipient } => {
+            assert_eq!(recipient, "addr0003")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // owner itself is always allowed
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("owner0001".to_string()),
+        amount: Some(Uint128::new(100000u128)),
+        recipients: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // without recipient, the principal recipient is used
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "wallet0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(400000u128),
+            }],
+        })]
+    );
+
+    // preferences are cleared when the owner is changed
+    let msg = ExecuteMsg::ChangeOwner {
+        new_owner: "owner0002".to_string(),
+        settle_previous_owner: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    env.block.time = Timestamp::from_seconds(110);
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("addr0003".to_string()),
+        amount: None,
+        recipients: None,
+    };
+    let info = mock_info("owner0002", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0003".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(500000u128),
+            }],
+        })]
+    );
+}
+
+#[test]
+fn distribute() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(300u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    // nothing vested yet and rewards distribution is disabled
+    let keeper = mock_info("keeper0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::NothingToDistribute {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SetDistribution {
+        distribute_rewards: true,
+        keeper_bounty: Decimal::percent(10),
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::SetDistribution {
+            distribute_rewards: true,
+            keeper_bounty: Decimal::percent(11),
+        },
+    )
+    .unwrap_err();
+    match res {
+        ContractError::InvalidKeeperBounty {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::SetClaimPreferences {
+            principal_recipient: Some("wallet0000".to_string()),
+            rewards_recipient: None,
+            allowed_recipients: None,
+        },
+    )
+    .unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // register rewards
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "wallet0000".to_string(),
+                    amount: Uint128::new(499999u128),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "owner0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(720u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "keeper0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(80u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "distribute"),
+            ("keeper", "keeper0000"),
+            ("recipient", "wallet0000"),
+            ("claim_amount", "499999"),
+            ("claim_index", "0"),
+            ("rewards_recipient", "owner0001"),
+            ("rewards_claim_amount", "720"),
+            ("claim_index", "1"),
+            ("keeper_bounty", "80"),
+            ("claim_index", "2"),
+        ]
+    );
+
+    // every transfer is kept in the claim history
+    assert_eq!(
+        from_binary::<ClaimHistoryResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ClaimHistory {
+                    start_after: Some(0),
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        ClaimHistoryResponse {
+            claims: vec![
+                ClaimRecord {
+                    index: 1,
+                    time: 105,
+                    kind: ClaimKind::Rewards,
+                    recipient: "owner0001".to_string(),
+                    denom: Denom::Native("uusd".to_string()),
+                    amount: Uint128::new(720),
+                },
+                ClaimRecord {
+                    index: 2,
+                    time: 105,
+                    kind: ClaimKind::Rewards,
+                    recipient: "keeper0000".to_string(),
+                    denom: Denom::Native("uusd".to_string()),
+                    amount: Uint128::new(80),
+                },
+            ],
+        }
+    );
+
+    // rate limited to once per vesting interval
+    env.block.time = Timestamp::from_seconds(109);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::DistributionTooEarly {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claimed_amount is shared with claim
+    env.block.time = Timestamp::from_seconds(110);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        keeper,
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {},).unwrap()
+        )
+        .unwrap()
+        .claimable_amount,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn operators() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: None,
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::AddOperator {
+        operator: "operator0000".to_string(),
+        permissions: OperatorPermissions {
+            claim: true,
+            claim_rewards: false,
+            staking: false,
+        },
+        expires: Some(Expiration::AtTime(Timestamp::from_seconds(108))),
+    };
+
+    // permission check
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![("action", "add_operator"), ("operator", "operator0000")]
+    );
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::AddOperator {
+            operator: "operator0001".to_string(),
+            permissions: OperatorPermissions {
+                claim: false,
+                claim_rewards: true,
+                staking: true,
+            },
+            expires: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_binary::<OperatorsResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Operators {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        OperatorsResponse {
+            operators: vec![
+                OperatorInfo {
+                    operator: "operator0000".to_string(),
+                    permissions: OperatorPermissions {
+                        claim: true,
+                        claim_rewards: false,
+                        staking: false,
+                    },
+                    expires: Expiration::AtTime(Timestamp::from_seconds(108)),
+                },
+                OperatorInfo {
+                    operator: "operator0001".to_string(),
+                    permissions: OperatorPermissions {
+                        claim: false,
+                        claim_rewards: true,
+                        staking: true,
+                    },
+                    expires: Expiration::Never {},
+                },
+            ],
+        }
+    );
+
+    assert_eq!(
+        from_binary::<OperatorsResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Operators {
+                    start_after: Some("operator0000".to_string()),
+                    limit: Some(1),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .operators
+        .len(),
+        1
+    );
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    // operator without claim permission
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let operator1 = mock_info("operator0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), operator1, msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // operator can only send to the owner or allowed recipients
+    let operator0 = mock_info("operator0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        operator0.clone(),
+        ExecuteMsg::Claim {
+            recipient: Some("operator0000".to_string()),
+            amount: None,
+            recipients: None,
+        },
+    )
+    .unwrap_err();
+    match res {
+        ContractError::RecipientNotAllowed { recipient } => {
+            assert_eq!(recipient, "operator0000")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // claim goes to the owner by default
+    let res = execute(deps.as_mut(), env.clone(), operator0.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "owner0001".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(500000u128),
+            }],
+        })]
+    );
+
+    // expired operator
+    env.block.time = Timestamp::from_seconds(110);
+    let res = execute(deps.as_mut(), env.clone(), operator0, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RemoveOperator {
+            operator: "operator0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![("action", "remove_operator"), ("operator", "operator0000")]
+    );
+
+    // operators are cleared when the owner is changed
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ChangeOwner {
+            new_owner: "owner0002".to_string(),
+            settle_previous_owner: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<OperatorsResponse>(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::Operators {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        OperatorsResponse { operators: vec![] }
+    );
+}
+
+#[test]
+fn shared_vesting() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: Some(vec![
+            BeneficiaryShare {
+                address: "founder0000".to_string(),
+                share: Uint128::new(1u128),
+            },
+            BeneficiaryShare {
+                address: "founder0001".to_string(),
+                share: Uint128::new(3u128),
+            },
+        ]),
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // make time to half claimable
+    env.block.time = Timestamp::from_seconds(105);
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+
+    // only beneficiaries can claim
+    let owner = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), owner.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let founder0 = mock_info("founder0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), founder0.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "founder0000".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(125000u128),
+            }],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim"),
+            ("beneficiary", "founder0000"),
+            ("recipient", "founder0000"),
+            ("vested_amount", "125000"),
+            ("claim_amount", "125000"),
+            ("claim_index", "0"),
+        ]
+    );
+
+    // split is not supported for shared vesting
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        founder0.clone(),
+        ExecuteMsg::Distribute {},
+    )
+    .unwrap_err();
+    match res {
+        ContractError::SharedNotSupported {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // reassign shares, founder0001 keeps what is vested so far
+    let msg = ExecuteMsg::UpdateBeneficiaries {
+        beneficiaries: vec![
+            BeneficiaryShare {
+                address: "founder0000".to_string(),
+                share: Uint128::new(1u128),
+            },
+            BeneficiaryShare {
+                address: "founder0002".to_string(),
+                share: Uint128::new(1u128),
+            },
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), founder0.clone(), msg.clone()).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env.clone(), owner, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![("action", "update_beneficiaries"), ("total_share", "2")]
+    );
+
+    // make time to fully claimable
+    env.block.time = Timestamp::from_seconds(110);
+
+    assert_eq!(
+        from_binary::<BeneficiariesResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Beneficiaries {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        BeneficiariesResponse {
+            beneficiaries: vec![
+                BeneficiaryResponse {
+                    address: "founder0000".to_string(),
+                    share: Uint128::new(1u128),
+                    vested_amount: Uint128::new(375000u128),
+                    claimed_amount: Uint128::new(125000u128),
+                    claimable_amount: Uint128::new(250000u128),
+                    claimed_staking_rewards: Uint128::zero(),
+                    claimable_staking_rewards: Uint128::zero(),
+                },
+                BeneficiaryResponse {
+                    address: "founder0001".to_string(),
+                    share: Uint128::zero(),
+                    vested_amount: Uint128::new(375000u128),
+                    claimed_amount: Uint128::zero(),
+                    claimable_amount: Uint128::new(375000u128),
+                    claimed_staking_rewards: Uint128::zero(),
+                    claimable_staking_rewards: Uint128::zero(),
+                },
+                BeneficiaryResponse {
+                    address: "founder0002".to_string(),
+                    share: Uint128::new(1u128),
+                    vested_amount: Uint128::new(250000u128),
+                    claimed_amount: Uint128::zero(),
+                    claimable_amount: Uint128::new(250000u128),
+                    claimed_staking_rewards: Uint128::zero(),
+                    claimable_staking_rewards: Uint128::zero(),
+                },
+            ],
+        }
+    );
+
+    let founder1 = mock_info("founder0001", &[]);
+    let msg = ExecuteMsg::Claim {
+        recipient: Some("addr0001".to_string()),
+        amount: None,
+        recipients: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), founder1, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(375000u128),
+            }],
+        })]
+    );
+
+    // total claimed amount is tracked as well
+    assert_eq!(
+        from_binary::<VestingInfoResponse>(
+            &query(deps.as_ref(), env, QueryMsg::VestingInfo {}).unwrap()
+        )
+        .unwrap()
+        .claimable_amount,
+        Uint128::new(500000u128)
+    );
+}
+
+#[test]
+fn shared_vesting_remainder() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::new(1000000u128),
+    }]);
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: false,
+        staking_info: None,
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: Some(
+            ["founder0000", "founder0001", "founder0002"]
+                .iter()
+                .map(|address| BeneficiaryShare {
+                    address: address.to_string(),
+                    share: Uint128::new(1u128),
+                })
+                .collect(),
+        ),
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    env.block.time = Timestamp::from_seconds(110);
+    let claimable_amounts = |deps: Deps, env: Env| {
+        from_binary::<BeneficiariesResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::Beneficiaries {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .beneficiaries
+        .into_iter()
+        .map(|b| b.claimable_amount.u128())
+        .collect::<Vec<u128>>()
+    };
+    assert_eq!(
+        claimable_amounts(deps.as_ref(), env.clone()),
+        vec![333333, 333333, 333333]
+    );
+
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    for founder in ["founder0000", "founder0001"] {
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(founder, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+    }
+
+    // the last claimer takes the rounding remainder
+    assert_eq!(
+        claimable_amounts(deps.as_ref(), env.clone()),
+        vec![0, 0, 333334]
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("founder0002", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "founder0002".to_string(),
+            amount: vec![Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(333334u128),
+            }],
+        })]
+    );
+
+    let res = execute(deps.as_mut(), env, mock_info("founder0002", &[]), msg).unwrap_err();
+    match res {
+        ContractError::NothingToClaim {} => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn shared_vesting_rewards() {
+    let mut deps = mock_dependencies_with_querier(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(300u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: Some(vec![
+            BeneficiaryShare {
+                address: "founder0000".to_string(),
+                share: Uint128::new(1u128),
+            },
+            BeneficiaryShare {
+                address: "founder0001".to_string(),
+                share: Uint128::new(1u128),
+            },
+        ]),
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(999999))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+    env.block.time = Timestamp::from_seconds(105);
+
+    // register rewards
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(500u128))]);
+
+    // rewards follow the unclaimed principal, 499999 of 999999 each
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: None,
+        recipients: None,
+    };
+    let founder0 = mock_info("founder0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), founder0.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "founder0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(399u128),
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            ("action", "claim_rewards"),
+            ("beneficiary", "founder0000"),
+            ("recipient", "founder0000"),
+            ("rewards_claim_amount", "399"),
+            ("claim_index", "0"),
+        ]
+    );
+
+    // rewards are withdrawn and partially paid out
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(401u128),
+        }],
+    );
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(51u128))]);
+
+    assert_eq!(
+        from_binary::<BeneficiaryResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Beneficiary {
+                    address: "founder0001".to_string(),
+                },
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        BeneficiaryResponse {
+            address: "founder0001".to_string(),
+            share: Uint128::new(1u128),
+            vested_amount: Uint128::new(249999u128),
+            claimed_amount: Uint128::zero(),
+            claimable_amount: Uint128::new(249999u128),
+            claimed_staking_rewards: Uint128::zero(),
+            claimable_staking_rewards: Uint128::new(425u128),
+        }
+    );
+
+    let claimable_rewards = |deps: Deps, env: Env, address: &str| {
+        from_binary::<BeneficiaryResponse>(
+            &query(
+                deps,
+                env,
+                QueryMsg::Beneficiary {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .claimable_staking_rewards
+    };
+    assert_eq!(
+        claimable_rewards(deps.as_ref(), env.clone(), "founder0000"),
+        Uint128::new(25u128),
+    );
+
+    // claimed principal stops earning rewards
+    env.block.time = Timestamp::from_seconds(110);
+    let claim_msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        founder0.clone(),
+        claim_msg.clone(),
+    )
+    .unwrap();
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(101u128))]);
+    assert_eq!(
+        claimable_rewards(deps.as_ref(), env.clone(), "founder0000"),
+        Uint128::new(25u128),
+    );
+    assert_eq!(
+        claimable_rewards(deps.as_ref(), env.clone(), "founder0001"),
+        Uint128::new(476u128),
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), founder0, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "founder0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(25u128),
+            }],
+        })]
+    );
+    deps.querier.with_native_balance(
+        MOCK_CONTRACT_ADDR,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(376u128),
+        }],
+    );
+
+    // the last claimer also takes the rounding remainder,
+    // 477 instead of 476 accrued through the reward index
+    let founder1 = mock_info("founder0001", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), founder1.clone(), claim_msg).unwrap();
+    assert_eq!(
+        claimable_rewards(deps.as_ref(), env.clone(), "founder0001"),
+        Uint128::new(477u128),
+    );
+    let res = execute(deps.as_mut(), env, founder1, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "reward".to_string(),
+                funds: vec![],
+                msg: to_binary(&RewardContractExecuteMsg::ClaimRewards { recipient: None })
+                    .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "founder0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(477u128),
+                }],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn shared_vesting_reconcile() {
+    let mut deps = mock_dependencies_with_querier(&[]);
+
+    let msg = InstantiateMsg {
+        owner_address: "owner0001".to_string(),
+        enable_staking: true,
+        staking_info: Some(StakingInfo {
+            bluna_token: "bluna".to_string(),
+            hub_contract: "hub".to_string(),
+            reward_contract: "reward".to_string(),
+        }),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        beneficiaries: Some(vec![
+            BeneficiaryShare {
+                address: "founder0000".to_string(),
+                share: Uint128::new(1u128),
+            },
+            BeneficiaryShare {
+                address: "founder0001".to_string(),
+                share: Uint128::new(1u128),
+            },
+        ]),
+        funder: None,
+        start_trigger: None,
+        admin: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(1000),
+        }],
+    );
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg: Reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+
+    deps.querier
+        .with_balances_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000))]);
+    let _res: Response = reply(deps.as_mut(), env.clone(), msg).unwrap();
+
+    // founder0000 claims 250 of its 500 bLUNA,
+    // and 1000 bLUNA is still held, 250 more than owed
+    env.block.time = Timestamp::from_seconds(105);
+    let msg = ExecuteMsg::Claim {
+        recipient: None,
+        amount: None,
+        recipients: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("founder0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // 150 rewards on 250 and 500 unclaimed bLUNA
+    deps.querier
+        .with_rewards_querier(&[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(150u128))]);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0001", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "reconcile"),
+            Attribute::new("balance", "1000"),
+            Attribute::new("surplus", "250"),
+            Attribute::new("vesting_amount", "1250"),
+        ]
+    );
+
+    // rewards accrued before the surplus is added are not reweighted
+    for (address, rewards) in [("founder0000", 50u128), ("founder0001", 100u128)] {
+        assert_eq!(
+            from_binary::<BeneficiaryResponse>(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::Beneficiary {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap()
+            )
+            .unwrap()
+            .claimable_staking_rewards,
+            Uint128::new(rewards),
+        );
+    }
+}
+
+/// Decode the trader, offer coin and ask denom of a market `MsgSwap`
+fn decode_msg_swap(value: &Binary) -> (String, (String, String), String) {
+    let mut trader = String::new();
+    let mut offer_coin = (String::new(), String::new());
+    let mut ask_denom = String::new();
+
+    // every field of MsgSwap and Coin is a string or a message
+    let read_field = |is: &mut protobuf::CodedInputStream| {
+        let (field, wire_type) = is.read_tag_unpack().unwrap();
+        assert_eq!(wire_type, WireType::WireTypeLengthDelimited);
+        field
+    };
+
+    let mut is = protobuf::CodedInputStream::from_bytes(value.as_slice());
+    while !is.eof().unwrap() {
+        match read_field(&mut is) {
+            1 => trader = is.read_string().unwrap(),
+            2 => {
+                let coin = is.read_bytes().unwrap();
+                let mut coin_is = protobuf::CodedInputStream::from_bytes(&coin);
+                while !coin_is.eof().unwrap() {
+                    match read_field(&mut coin_is) {
+                        1 => offer_coin.0 = coin_is.read_string().unwrap(),
+                        2 => offer_coin.1 = coin_is.read_string().unwrap(),
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                }
+            }
+            3 => ask_denom = is.read_string().unwrap(),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    (trader, offer_coin, ask_denom)
+}
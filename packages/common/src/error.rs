@@ -0,0 +1,173 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+/// Errors returned by the vesting, vesting pool and vesting factory contracts
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    // authorization
+    #[error("unauthorized")]
+    Unauthorized {},
+
+    #[error("recipient not allowed: {recipient}")]
+    RecipientNotAllowed { recipient: String },
+
+    #[error("cannot add owner as operator")]
+    OwnerAsOperator {},
+
+    #[error("operator not found")]
+    OperatorNotFound {},
+
+    #[error("expiration already passed")]
+    ExpirationPassed {},
+
+    #[error("unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    // schedule validation
+    #[error("invalid start_time")]
+    InvalidStartTime {},
+
+    #[error("invalid end_time")]
+    InvalidEndTime {},
+
+    #[error("invalid vesting_interval")]
+    InvalidVestingInterval {},
+
+    #[error("end_time must be bigger than start_time")]
+    EndTimeBeforeStartTime {},
+
+    #[error("vesting_interval must be non-zero")]
+    ZeroVestingInterval {},
+
+    #[error("(end_time - start_time) must be multiple of vesting_interval")]
+    UnalignedVestingPeriod {},
+
+    #[error("vesting already started")]
+    VestingAlreadyStarted {},
+
+    #[error("vesting not started")]
+    VestingNotStarted {},
+
+    #[error("vesting already suspended")]
+    AlreadySuspended {},
+
+    #[error("vesting not suspended")]
+    NotSuspended {},
+
+    // funds
+    #[error("only {denom} is allowed to be deposited")]
+    InvalidDeposit { denom: String },
+
+    #[error("cannot make zero token vesting account")]
+    ZeroVestingAmount {},
+
+    #[error("nothing to claim")]
+    NothingToClaim {},
+
+    #[error("claim amount must be non-zero")]
+    ZeroClaimAmount {},
+
+    #[error("claim amount exceeds claimable amount")]
+    ClaimAmountExceeded {},
+
+    #[error("cannot specify both recipient and recipients")]
+    ConflictingRecipients {},
+
+    #[error("recipients must not be empty")]
+    EmptyRecipients {},
+
+    #[error("recipient amounts exceed claim amount")]
+    RecipientAmountsExceeded {},
+
+    #[error("recipient amounts must add up to claim amount")]
+    RecipientAmountsMismatch {},
+
+    #[error("beneficiaries must not be empty")]
+    EmptyBeneficiaries {},
+
+    #[error("duplicated beneficiary: {address}")]
+    DuplicatedBeneficiary { address: String },
+
+    #[error("share must be non-zero")]
+    ZeroShare {},
+
+    #[error("nothing to distribute")]
+    NothingToDistribute {},
+
+    #[error("distribution is allowed once per vesting interval")]
+    DistributionTooEarly {},
+
+    #[error("keeper_bounty must be smaller than or equal to 10%")]
+    InvalidKeeperBounty {},
+
+    #[error("no surplus to reconcile")]
+    NoSurplus {},
+
+    #[error("nothing to sweep")]
+    NothingToSweep {},
+
+    #[error("sweep would take principal")]
+    SweepTakesPrincipal {},
+
+    // staking
+    #[error("staking is not enabled")]
+    StakingDisabled {},
+
+    #[error("staking_info is required to enable staking")]
+    MissingStakingInfo {},
+
+    #[error("compounding is not enabled")]
+    CompoundingDisabled {},
+
+    #[error("nothing to bond")]
+    NothingToBond {},
+
+    #[error("nothing to compound")]
+    NothingToCompound {},
+
+    #[error("swap returned {amount} uluna, less than the minimum {minimum_receive}")]
+    MinimumReceiveNotMet {
+        amount: Uint128,
+        minimum_receive: Uint128,
+    },
+
+    #[error("nothing to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("rewards position must be withdrawn first")]
+    RewardsPositionOpen {},
+
+    #[error("recipient is not supported by the rewards strategy")]
+    RecipientNotSupported {},
+
+    // accounts
+    #[error("already exist")]
+    AlreadyExists {},
+
+    #[error("vesting account not found")]
+    VestingAccountNotFound {},
+
+    #[error("vesting contract not found")]
+    VestingContractNotFound {},
+
+    #[error("beneficiaries are not supported by vesting pool")]
+    BeneficiariesNotSupported {},
+
+    #[error("deferred start is not supported by vesting pool")]
+    DeferredStartNotSupported {},
+
+    #[error("suspension is not supported by vesting pool")]
+    SuspensionNotSupported {},
+
+    #[error("not a shared vesting contract")]
+    NotShared {},
+
+    #[error("not supported by shared vesting contract")]
+    SharedNotSupported {},
+}
@@ -0,0 +1,101 @@
+use cosmwasm_std::{Event, Uint128};
+use cw20::Denom;
+
+/// Event emitted when the vesting amount of an account is set
+pub const VESTING_CREATED: &str = "vesting_created";
+/// Event emitted for each principal payout
+pub const VESTING_CLAIMED: &str = "vesting_claimed";
+/// Event emitted for each staking rewards payout
+pub const REWARDS_CLAIMED: &str = "rewards_claimed";
+/// Event emitted when an account moves to a new owner
+pub const OWNER_CHANGED: &str = "owner_changed";
+
+/// `native` or `cw20`
+pub fn denom_kind(denom: &Denom) -> &'static str {
+    match denom {
+        Denom::Native(_) => "native",
+        Denom::Cw20(_) => "cw20",
+    }
+}
+
+/// Native denom or cw20 token address
+pub fn denom_name(denom: &Denom) -> String {
+    match denom {
+        Denom::Native(denom) => denom.to_string(),
+        Denom::Cw20(contract_addr) => contract_addr.to_string(),
+    }
+}
+
+fn denom_event(ty: &str, contract: &str, owner: &str, denom: &Denom) -> Event {
+    Event::new(ty)
+        .add_attribute("contract", contract)
+        .add_attribute("owner", owner)
+        .add_attribute("denom_kind", denom_kind(denom))
+        .add_attribute("denom", denom_name(denom))
+}
+
+/// `vesting_created`: `contract`, `owner`, `denom_kind`, `denom`, `amount`
+pub fn vesting_created(contract: &str, owner: &str, denom: &Denom, amount: Uint128) -> Event {
+    denom_event(VESTING_CREATED, contract, owner, denom).add_attribute("amount", amount)
+}
+
+/// `vesting_claimed`: `contract`, `owner`, `denom_kind`, `denom`,
+/// `address` (the recipient), `amount`
+pub fn vesting_claimed(
+    contract: &str,
+    owner: &str,
+    denom: &Denom,
+    address: &str,
+    amount: Uint128,
+) -> Event {
+    denom_event(VESTING_CLAIMED, contract, owner, denom)
+        .add_attribute("address", address)
+        .add_attribute("amount", amount)
+}
+
+/// `rewards_claimed`: `contract`, `owner`, `denom_kind`, `denom`,
+/// `address` (the recipient), `amount`
+pub fn rewards_claimed(
+    contract: &str,
+    owner: &str,
+    denom: &Denom,
+    address: &str,
+    amount: Uint128,
+) -> Event {
+    denom_event(REWARDS_CLAIMED, contract, owner, denom)
+        .add_attribute("address", address)
+        .add_attribute("amount", amount)
+}
+
+/// `owner_changed`: `contract`, `owner` (the new owner), `previous_owner`
+pub fn owner_changed(contract: &str, owner: &str, previous_owner: &str) -> Event {
+    Event::new(OWNER_CHANGED)
+        .add_attribute("contract", contract)
+        .add_attribute("owner", owner)
+        .add_attribute("previous_owner", previous_owner)
+}
+
+#[test]
+fn claimed_event_attributes() {
+    use cosmwasm_std::{Addr, Attribute};
+
+    let event = vesting_claimed(
+        "vesting0000",
+        "owner0000",
+        &Denom::Cw20(Addr::unchecked("bluna")),
+        "addr0000",
+        Uint128::new(100u128),
+    );
+    assert_eq!(event.ty, "vesting_claimed");
+    assert_eq!(
+        event.attributes,
+        vec![
+            Attribute::new("contract", "vesting0000"),
+            Attribute::new("owner", "owner0000"),
+            Attribute::new("denom_kind", "cw20"),
+            Attribute::new("denom", "bluna"),
+            Attribute::new("address", "addr0000"),
+            Attribute::new("amount", "100"),
+        ]
+    );
+}
@@ -1,2 +1,9 @@
+pub mod error;
+pub mod events;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mock_querier;
+pub mod staking;
+pub mod tax;
 pub mod vesting;
 pub mod vesting_factory;
+pub mod vesting_pool;
@@ -3,13 +3,14 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
     QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use cw20::BalanceResponse;
 use std::collections::HashMap;
 
-use crate::external::handle::AccruedRewardsResponse;
+use crate::staking::{AccruedRewardsResponse, HubStateResponse};
+use crate::tax::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper};
 
 /// mock_dependencies_with_querier is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -30,6 +31,10 @@ pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     rewards_querier: RewardsQuerier,
     balances_querier: BalancesQuerier,
+    token_balances: HashMap<(String, String), Uint128>,
+    treasury_querier: TreasuryQuerier,
+    exchange_rate: Decimal,
+    failing_contracts: Vec<String>,
 }
 
 #[derive(Clone, Default)]
@@ -45,6 +50,21 @@ impl RewardsQuerier {
     }
 }
 
+#[derive(Clone, Default)]
+pub struct TreasuryQuerier {
+    tax_rate: Decimal,
+    tax_caps: HashMap<String, Uint128>,
+}
+
+impl TreasuryQuerier {
+    pub fn new(tax_rate: Decimal, tax_caps: &[(&String, &Uint128)]) -> Self {
+        TreasuryQuerier {
+            tax_rate,
+            tax_caps: balances_to_map(tax_caps),
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct BalancesQuerier {
     balances: HashMap<String, Uint128>,
@@ -58,17 +78,23 @@ impl BalancesQuerier {
     }
 }
 
-pub(crate) fn balances_to_map(rewards: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
+fn balances_to_map(rewards: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
     let mut rewards_map: HashMap<String, Uint128> = HashMap::new();
     for (key, reward) in rewards.iter() {
-        rewards_map.insert(key.to_string(), (*reward).clone());
+        rewards_map.insert(key.to_string(), **reward);
     }
     rewards_map
 }
 
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
-        // MockQuerier doesn't support Custom, so we ignore it completely here
+        // MockQuerier doesn't support Custom, so Terra queries are handled here
+        if let Ok(QueryRequest::Custom(TerraQueryWrapper { query_data, .. })) =
+            from_slice::<QueryRequest<TerraQueryWrapper>>(bin_request)
+        {
+            return self.handle_terra_query(query_data);
+        }
+
         let request: QueryRequest<Empty> = match from_slice(bin_request) {
             Ok(v) => v,
             Err(e) => {
@@ -89,19 +115,47 @@ pub enum WasmQueryMsg {
     AccruedRewards { address: String },
     /// Request cw20 token balance
     Balance { address: String },
+    /// Request hub state
+    State {},
 }
 
 impl WasmMockQuerier {
+    fn handle_terra_query(&self, query_data: TerraQuery) -> QuerierResult {
+        match query_data {
+            TerraQuery::TaxRate {} => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&TaxRateResponse {
+                    rate: self.treasury_querier.tax_rate,
+                })
+                .unwrap(),
+            )),
+            TerraQuery::TaxCap { denom } => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&TaxCapResponse {
+                    cap: self
+                        .treasury_querier
+                        .tax_caps
+                        .get(&denom)
+                        .copied()
+                        .unwrap_or_default(),
+                })
+                .unwrap(),
+            )),
+        }
+    }
+
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
-            QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: _,
-                msg,
-            }) => match from_binary(&msg).unwrap() {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, .. })
+                if self.failing_contracts.contains(contract_addr) =>
+            {
+                SystemResult::Ok(ContractResult::Err(format!("{} is paused", contract_addr)))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg)
+                .unwrap()
+            {
                 WasmQueryMsg::AccruedRewards { address } => {
                     match self.rewards_querier.accrued_rewards.get(&address) {
                         Some(v) => SystemResult::Ok(ContractResult::Ok(
-                            to_binary(&AccruedRewardsResponse { rewards: v.clone() }).unwrap(),
+                            to_binary(&AccruedRewardsResponse { rewards: *v }).unwrap(),
                         )),
                         None => SystemResult::Ok(ContractResult::Ok(
                             to_binary(&AccruedRewardsResponse {
@@ -112,9 +166,12 @@ impl WasmMockQuerier {
                     }
                 }
                 WasmQueryMsg::Balance { address } => {
-                    match self.balances_querier.balances.get(&address) {
+                    let token_balance = self
+                        .token_balances
+                        .get(&(contract_addr.to_string(), address.to_string()));
+                    match token_balance.or_else(|| self.balances_querier.balances.get(&address)) {
                         Some(v) => SystemResult::Ok(ContractResult::Ok(
-                            to_binary(&BalanceResponse { balance: v.clone() }).unwrap(),
+                            to_binary(&BalanceResponse { balance: *v }).unwrap(),
                         )),
                         None => SystemResult::Ok(ContractResult::Ok(
                             to_binary(&BalanceResponse {
@@ -124,6 +181,12 @@ impl WasmMockQuerier {
                         )),
                     }
                 }
+                WasmQueryMsg::State {} => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&HubStateResponse {
+                        exchange_rate: self.exchange_rate,
+                    })
+                    .unwrap(),
+                )),
             },
             _ => self.base.handle_query(request),
         }
@@ -136,6 +199,10 @@ impl WasmMockQuerier {
             base,
             rewards_querier: RewardsQuerier::default(),
             balances_querier: BalancesQuerier::default(),
+            token_balances: HashMap::new(),
+            treasury_querier: TreasuryQuerier::default(),
+            exchange_rate: Decimal::one(),
+            failing_contracts: vec![],
         }
     }
 
@@ -143,7 +210,35 @@ impl WasmMockQuerier {
         self.rewards_querier = RewardsQuerier::new(rewards);
     }
 
+    pub fn with_native_balance(&mut self, address: &str, balance: &[Coin]) {
+        self.base.update_balance(address, balance.to_vec());
+    }
+
     pub fn with_balances_querier(&mut self, balances: &[(&String, &Uint128)]) {
         self.balances_querier = BalancesQuerier::new(balances);
     }
+
+    pub fn with_exchange_rate(&mut self, exchange_rate: Decimal) {
+        self.exchange_rate = exchange_rate;
+    }
+
+    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+        self.treasury_querier = TreasuryQuerier::new(rate, caps);
+    }
+
+    /// Every query to `contract` fails
+    pub fn with_failing_contract(&mut self, contract: &str) {
+        self.failing_contracts.push(contract.to_string());
+    }
+
+    /// Queries to `contract` succeed again
+    pub fn with_recovered_contract(&mut self, contract: &str) {
+        self.failing_contracts.retain(|c| c != contract);
+    }
+
+    /// Balance of `token` only, taking precedence over `with_balances_querier`
+    pub fn with_token_balance(&mut self, token: &str, address: &str, balance: Uint128) {
+        self.token_balances
+            .insert((token.to_string(), address.to_string()), balance);
+    }
 }
@@ -0,0 +1,54 @@
+use cosmwasm_std::{Decimal, Deps, StdResult, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardContractExecuteMsg {
+    /// Request bAsset reward withdrawal
+    ClaimRewards { recipient: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardContractQueryMsg {
+    /// Request bAsset reward amount
+    AccruedRewards { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HubContractExecuteMsg {
+    /// Receives `amount` in underlying coin denom from sender.
+    /// Delegate `amount` equally between validators from the registry.
+    /// Issue `amount` / exchange_rate for the user.
+    Bond {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HubContractQueryMsg {
+    State {},
+}
+
+/// Part of the hub `State` response used here
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HubStateResponse {
+    /// LUNA per bLUNA
+    pub exchange_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AccruedRewardsResponse {
+    pub rewards: Uint128,
+}
+
+/// LUNA per bLUNA at the hub contract
+pub fn query_exchange_rate(deps: Deps, hub_contract: &str) -> StdResult<Decimal> {
+    let response: HubStateResponse = deps
+        .querier
+        .query_wasm_smart(hub_contract, &HubContractQueryMsg::State {})?;
+
+    Ok(response.exchange_rate)
+}
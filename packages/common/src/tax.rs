@@ -0,0 +1,153 @@
+use cosmwasm_std::{Coin, CustomQuery, Decimal, QuerierWrapper, QueryRequest, StdResult, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// LUNA is exempt from the stability tax
+const TAX_EXEMPT_DENOM: &str = "uluna";
+const DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
+
+/// Terra custom query, only the treasury route is used
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TerraQueryWrapper {
+    pub route: TerraRoute,
+    pub query_data: TerraQuery,
+}
+
+impl CustomQuery for TerraQueryWrapper {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerraRoute {
+    Treasury,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerraQuery {
+    TaxRate {},
+    TaxCap { denom: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TaxRateResponse {
+    pub rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TaxCapResponse {
+    pub cap: Uint128,
+}
+
+/// TaxQuerier provides the stability tax parameters,
+/// so the tax can be computed against any querier.
+pub trait TaxQuerier {
+    fn query_tax_rate(&self) -> StdResult<Decimal>;
+    fn query_tax_cap(&self, denom: &str) -> StdResult<Uint128>;
+}
+
+/// Queries Terra's treasury module
+impl TaxQuerier for QuerierWrapper<'_> {
+    fn query_tax_rate(&self) -> StdResult<Decimal> {
+        let response: TaxRateResponse =
+            self.custom_query(&QueryRequest::Custom(TerraQueryWrapper {
+                route: TerraRoute::Treasury,
+                query_data: TerraQuery::TaxRate {},
+            }))?;
+
+        Ok(response.rate)
+    }
+
+    fn query_tax_cap(&self, denom: &str) -> StdResult<Uint128> {
+        let response: TaxCapResponse =
+            self.custom_query(&QueryRequest::Custom(TerraQueryWrapper {
+                route: TerraRoute::Treasury,
+                query_data: TerraQuery::TaxCap {
+                    denom: denom.to_string(),
+                },
+            }))?;
+
+        Ok(response.cap)
+    }
+}
+
+/// Tax charged when `coin` leaves the contract, for `coin.amount`
+/// including the tax. The tax is `amount * rate / (1 + rate)`
+/// rounded up, and at most the tax cap of the denom.
+pub fn compute_tax(querier: &dyn TaxQuerier, coin: &Coin) -> StdResult<Uint128> {
+    if coin.denom == TAX_EXEMPT_DENOM || coin.amount.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let tax_rate = querier.query_tax_rate()?;
+    let tax_cap = querier.query_tax_cap(&coin.denom)?;
+
+    let sendable = coin.amount.multiply_ratio(
+        DECIMAL_FRACTION,
+        DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
+    );
+    Ok(std::cmp::min(coin.amount.checked_sub(sendable)?, tax_cap))
+}
+
+/// `coin` with the tax deducted, so sending it costs exactly `coin.amount`
+pub fn deduct_tax(querier: &dyn TaxQuerier, coin: Coin) -> StdResult<Coin> {
+    let tax = compute_tax(querier, &coin)?;
+    Ok(Coin {
+        denom: coin.denom,
+        amount: coin.amount.checked_sub(tax)?,
+    })
+}
+
+#[cfg(test)]
+struct FixedTax {
+    rate: Decimal,
+    cap: Uint128,
+}
+
+#[cfg(test)]
+impl TaxQuerier for FixedTax {
+    fn query_tax_rate(&self) -> StdResult<Decimal> {
+        Ok(self.rate)
+    }
+
+    fn query_tax_cap(&self, _denom: &str) -> StdResult<Uint128> {
+        Ok(self.cap)
+    }
+}
+
+#[cfg(test)]
+fn coin(amount: u128, denom: &str) -> Coin {
+    Coin {
+        denom: denom.to_string(),
+        amount: Uint128::new(amount),
+    }
+}
+
+#[test]
+fn deduct_tax_with_rate_and_cap() {
+    let querier = FixedTax {
+        rate: Decimal::permille(5),
+        cap: Uint128::new(1000000),
+    };
+
+    // 1005 = 1000 + 1000 * 0.005
+    assert_eq!(
+        deduct_tax(&querier, coin(1005, "uusd")).unwrap(),
+        coin(1000, "uusd")
+    );
+    // rounding goes to the tax
+    assert_eq!(
+        deduct_tax(&querier, coin(1000, "uusd")).unwrap(),
+        coin(995, "uusd")
+    );
+    // capped
+    assert_eq!(
+        deduct_tax(&querier, coin(1000000000, "uusd")).unwrap(),
+        coin(999000000, "uusd")
+    );
+    // LUNA is exempt
+    assert_eq!(
+        deduct_tax(&querier, coin(1000, "uluna")).unwrap(),
+        coin(1000, "uluna")
+    );
+}
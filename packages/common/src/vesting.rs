@@ -1,28 +1,164 @@
-use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
-use cw20::Denom;
+use cosmwasm_std::{
+    to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Fraction, StdResult, Uint128, Uint64,
+    WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Denom, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ContractError;
+use crate::tax::{deduct_tax, TaxQuerier};
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner_address: String,
     pub enable_staking: bool,
     pub staking_info: Option<StakingInfo>,
     pub vesting_schedule: VestingSchedule,
+    /// When given, the vesting amount and staking rewards are shared
+    /// among the beneficiaries and `owner_address` manages the shares
+    pub beneficiaries: Option<Vec<BeneficiaryShare>>,
+    /// Allowed to `Sweep` along with the owner, the sender by default
+    pub funder: Option<String>,
+    /// When given, `vesting_schedule` is relative to a start set later
+    /// by this address with `StartVesting`. Nothing vests before then.
+    pub start_trigger: Option<String>,
+    /// Allowed to `Suspend` and `Resume` along with the funder
+    pub admin: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    ChangeOwner { new_owner: String },
-    Claim { recipient: Option<String> },
-    ClaimRewards { recipient: Option<String> },
+    ChangeOwner {
+        new_owner: String,
+        /// Pay the outgoing owner everything vested and accrued
+        /// up to this block, true by default
+        settle_previous_owner: Option<bool>,
+    },
+    Claim {
+        recipient: Option<String>,
+        amount: Option<Uint128>,
+        recipients: Option<Vec<ClaimRecipient>>,
+    },
+    /// Claim vested tokens and send them to `contract` with `msg`
+    /// (cw20 `Send` hook for bLUNA, execute with funds for LUNA)
+    ClaimAndSend {
+        contract: String,
+        msg: Binary,
+        amount: Option<Uint128>,
+    },
+    ClaimRewards {
+        recipient: Option<String>,
+        recipients: Option<Vec<ClaimRecipient>>,
+    },
+    /// Store default recipients and the destination allowlist
+    SetClaimPreferences {
+        principal_recipient: Option<String>,
+        rewards_recipient: Option<String>,
+        allowed_recipients: Option<Vec<String>>,
+    },
+    /// Push claimable tokens to the configured recipients,
+    /// executable by anyone once per vesting interval
+    Distribute {},
+    SetDistribution {
+        distribute_rewards: bool,
+        keeper_bounty: Decimal,
+    },
+    /// Grant `operator` the given permissions until `expires`,
+    /// replacing any existing grant
+    AddOperator {
+        operator: String,
+        permissions: OperatorPermissions,
+        expires: Option<Expiration>,
+    },
+    RemoveOperator {
+        operator: String,
+    },
+    /// Reassign shares of a shared vesting contract. Beneficiaries
+    /// not in the list keep what they have accrued so far.
+    UpdateBeneficiaries {
+        beneficiaries: Vec<BeneficiaryShare>,
+    },
+    /// Enable compounding of staking rewards, or disable it with `None`
+    SetCompounding {
+        compounding: Option<CompoundConfig>,
+    },
+    /// Swap pending staking rewards of the owner to uluna
+    /// and bond them into the vesting position.
+    /// Fails when the swap returns less than `minimum_receive` uluna.
+    Compound {
+        minimum_receive: Option<Uint128>,
+    },
+    /// Only allowed while the rewards position is empty
+    SetRewardsStrategy {
+        rewards_strategy: RewardsStrategy,
+    },
+    /// Send the rewards position (uusd or aUST) to `recipient`
+    WithdrawRewardsPosition {
+        recipient: Option<String>,
+    },
+    /// Add principal held beyond the unclaimed amount
+    /// to the vesting position
+    Reconcile {},
+    /// Send `asset` held beyond the unclaimed principal and the
+    /// rewards owed to `recipient` (the sender by default)
+    Sweep {
+        asset: Denom,
+        recipient: Option<String>,
+    },
+    /// Start a deferred vesting at the current block time,
+    /// executable by the `start_trigger` only
+    StartVesting {},
+    /// Stop the vesting from advancing until `Resume`,
+    /// executable by the funder or the admin
+    Suspend {},
+    /// Continue a suspended vesting, pushing the schedule back
+    /// by the suspended duration
+    Resume {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     VestingInfo {},
+    Operators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Beneficiary {
+        address: String,
+    },
+    Beneficiaries {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Solvency {},
+    /// Vesting account status at `time` (in seconds), past or future.
+    /// Staking rewards are not queried.
+    VestingInfoAt {
+        time: u64,
+    },
+    /// Amount vested at `time` (in seconds)
+    VestedAmount {
+        time: u64,
+    },
+    /// Unlock events after `start_after` (in seconds), past and upcoming
+    UnlockSchedule {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    NextUnlock {},
+    /// Claims of principal and staking rewards in the order made
+    ClaimHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Staking rewards only, fails when the staking contracts fail
+    Rewards {},
+    Config {},
+    /// Suspended periods, the last one open while suspended
+    Suspensions {},
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
@@ -34,6 +170,370 @@ pub struct VestingInfoResponse {
     pub vesting_schedule: VestingSchedule,
     pub claimable_amount: Uint128,
     pub claimable_staking_rewards: Uint128,
+    /// LUNA equivalent of the position, only for staking enabled accounts
+    pub luna_value: Option<LunaValueResponse>,
+    /// Error from the staking contracts, in which case
+    /// the staking rewards and `luna_value` are left empty
+    pub rewards_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct RewardsResponse {
+    /// Pending rewards of the owner, or of all beneficiaries when shared
+    pub claimable_staking_rewards: Uint128,
+    /// uusd or aUST kept by the `hold` and `deposit_to_market` strategies
+    pub rewards_position: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct ConfigResponse {
+    pub owner_address: String,
+    pub funder: String,
+    pub staking_enabled: bool,
+    pub staking_info: Option<StakingInfo>,
+    pub claim_preferences: ClaimPreferences,
+    pub distribution: DistributionConfig,
+    pub compounding: Option<CompoundConfig>,
+    pub rewards_strategy: RewardsStrategy,
+    /// Set until a deferred vesting is started
+    pub start_trigger: Option<String>,
+    pub admin: Option<String>,
+}
+
+/// Vesting did not advance from `start_time` to `end_time`,
+/// which is empty while still suspended
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SuspendedPeriod {
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct SuspensionsResponse {
+    pub suspended_periods: Vec<SuspendedPeriod>,
+    /// Total suspended seconds up to the current block,
+    /// by which the schedule is pushed back
+    pub suspended_duration: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct VestingInfoAtResponse {
+    pub time: u64,
+    pub owner_address: String,
+    pub vesting_denom: Denom,
+    pub vesting_amount: Uint128,
+    pub vested_amount: Uint128,
+    /// Amount claimed as of now
+    pub claimed_amount: Uint128,
+    /// Zero for a past `time` vested less than claimed as of now
+    pub claimable_amount: Uint128,
+    pub vesting_schedule: VestingSchedule,
+}
+
+/// Vested amount increases by `amount` to `cumulative_amount` at `time`
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct UnlockResponse {
+    pub time: u64,
+    pub amount: Uint128,
+    pub cumulative_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct UnlockScheduleResponse {
+    pub unlocks: Vec<UnlockResponse>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct NextUnlockResponse {
+    /// `None` once fully vested
+    pub unlock: Option<UnlockResponse>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct VestedAmountResponse {
+    pub time: u64,
+    pub vested_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimKind {
+    Principal,
+    Rewards,
+}
+
+/// A transfer of principal or staking rewards out of the contract.
+/// `amount` is before the stability tax.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    pub index: u64,
+    pub time: u64,
+    pub kind: ClaimKind,
+    pub recipient: String,
+    pub denom: Denom,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct ClaimHistoryResponse {
+    pub claims: Vec<ClaimRecord>,
+}
+
+/// LUNA equivalent of a bLUNA vesting position at the hub exchange rate
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct LunaValueResponse {
+    pub exchange_rate: Decimal,
+    /// Exchange rate when the bLUNA was bonded
+    pub bond_exchange_rate: Decimal,
+    pub vesting_amount: Uint128,
+    pub vested_amount: Uint128,
+    pub claimable_amount: Uint128,
+    /// LUNA lost by slashing since the bLUNA was bonded
+    pub slashing_loss: Uint128,
+}
+
+impl LunaValueResponse {
+    /// Convert bLUNA amounts to LUNA at `exchange_rate`
+    pub fn new(
+        exchange_rate: Decimal,
+        bond_exchange_rate: Decimal,
+        vesting_amount: Uint128,
+        vested_amount: Uint128,
+        claimable_amount: Uint128,
+    ) -> Self {
+        let bonded_value = vesting_amount * bond_exchange_rate;
+        let vesting_value = vesting_amount * exchange_rate;
+
+        LunaValueResponse {
+            exchange_rate,
+            bond_exchange_rate,
+            vesting_amount: vesting_value,
+            vested_amount: vested_amount * exchange_rate,
+            claimable_amount: claimable_amount * exchange_rate,
+            slashing_loss: bonded_value.saturating_sub(vesting_value),
+        }
+    }
+}
+
+/// Principal held by the contract against what is still owed.
+/// With a deficit (e.g. after slashing) claims are paid out pro rata.
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct SolvencyResponse {
+    pub vesting_denom: Denom,
+    pub balance: Uint128,
+    /// Vesting amount not claimed yet
+    pub obligation: Uint128,
+    pub surplus: Uint128,
+    pub deficit: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct BeneficiaryResponse {
+    pub address: String,
+    pub share: Uint128,
+    pub vested_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub claimable_amount: Uint128,
+    pub claimed_staking_rewards: Uint128,
+    pub claimable_staking_rewards: Uint128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct BeneficiariesResponse {
+    pub beneficiaries: Vec<BeneficiaryResponse>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct OperatorsResponse {
+    pub operators: Vec<OperatorInfo>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct OperatorInfo {
+    pub operator: String,
+    pub permissions: OperatorPermissions,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BeneficiaryShare {
+    pub address: String,
+    pub share: Uint128,
+}
+
+/// OperatorPermissions is used to decide which actions an operator
+/// can execute on behalf of the owner. Operators can only send
+/// tokens to the owner or the owner's configured recipients.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OperatorPermissions {
+    pub claim: bool,
+    pub claim_rewards: bool,
+    pub staking: bool,
+}
+
+/// ClaimPreferences is used to route claims without passing
+/// `recipient` on every call. When `allowed_recipients` is set,
+/// tokens can only be sent to the owner or the listed addresses.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimPreferences {
+    pub principal_recipient: Option<String>,
+    pub rewards_recipient: Option<String>,
+    pub allowed_recipients: Option<Vec<String>>,
+}
+
+impl ClaimPreferences {
+    pub fn check_recipient(
+        &self,
+        owner_address: &str,
+        recipient: &str,
+    ) -> Result<(), ContractError> {
+        match &self.allowed_recipients {
+            Some(allowed_recipients)
+                if recipient != owner_address
+                    && !allowed_recipients.iter().any(|a| a == recipient) =>
+            {
+                Err(ContractError::RecipientNotAllowed {
+                    recipient: recipient.to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Operators can only send tokens to the owner, the default
+    /// recipients and the allowlisted addresses.
+    pub fn check_operator_recipient(
+        &self,
+        owner_address: &str,
+        recipient: &str,
+    ) -> Result<(), ContractError> {
+        let allowed = recipient == owner_address
+            || self.principal_recipient.as_deref() == Some(recipient)
+            || self.rewards_recipient.as_deref() == Some(recipient)
+            || self
+                .allowed_recipients
+                .iter()
+                .flatten()
+                .any(|a| a == recipient);
+        if !allowed {
+            return Err(ContractError::RecipientNotAllowed {
+                recipient: recipient.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// DistributionConfig decides whether `Distribute` also pushes
+/// staking rewards and which portion of them is paid to the caller.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DistributionConfig {
+    pub distribute_rewards: bool,
+    pub keeper_bounty: Decimal,
+}
+
+/// RewardsStrategy decides what `ClaimRewards` does with the owner's
+/// staking rewards. Except for `Payout`, rewards are kept in this
+/// contract as the rewards position until `WithdrawRewardsPosition`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardsStrategy {
+    /// Send uusd to the recipient
+    #[default]
+    Payout,
+    /// Keep uusd in this contract
+    Hold,
+    /// Deposit uusd to Anchor money market and keep the received aUST
+    DepositToMarket {
+        market_contract: String,
+        aterra_token: String,
+    },
+}
+
+/// ClaimRecipient is used to split a claim
+/// across multiple addresses.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ClaimRecipient {
+    pub address: String,
+    pub share: ClaimShare,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimShare {
+    /// weight of the amount left after fixed amounts are paid,
+    /// divided by the total weight of all ratio shares
+    Ratio(Decimal),
+    /// fixed amount
+    Amount(Uint128),
+}
+
+/// Returns the sum of the fixed amounts when every recipient
+/// is given a fixed amount, which is then the whole claim amount.
+pub fn fixed_claim_amount(recipients: &[ClaimRecipient]) -> Option<Uint128> {
+    recipients
+        .iter()
+        .map(|r| match r.share {
+            ClaimShare::Amount(amount) => Some(amount),
+            ClaimShare::Ratio(_) => None,
+        })
+        .sum()
+}
+
+/// Split `amount` across `recipients`. Fixed amounts are paid first and the
+/// rest is divided by ratio weights; rounding remainders go to the first recipient.
+pub fn split_claim(
+    amount: Uint128,
+    recipients: &[ClaimRecipient],
+) -> Result<Vec<(String, Uint128)>, ContractError> {
+    if recipients.is_empty() {
+        return Err(ContractError::EmptyRecipients {});
+    }
+
+    let fixed_amount: Uint128 = recipients
+        .iter()
+        .map(|r| match r.share {
+            ClaimShare::Amount(amount) => amount,
+            ClaimShare::Ratio(_) => Uint128::zero(),
+        })
+        .sum();
+    let ratio_amount = amount
+        .checked_sub(fixed_amount)
+        .map_err(|_| ContractError::RecipientAmountsExceeded {})?;
+
+    let total_ratio = recipients
+        .iter()
+        .fold(Decimal::zero(), |acc, r| match r.share {
+            ClaimShare::Ratio(ratio) => acc + ratio,
+            ClaimShare::Amount(_) => acc,
+        });
+    if total_ratio.is_zero() && !ratio_amount.is_zero() {
+        return Err(ContractError::RecipientAmountsMismatch {});
+    }
+
+    let mut shares: Vec<(String, Uint128)> = recipients
+        .iter()
+        .map(|r| {
+            let share = match r.share {
+                ClaimShare::Amount(amount) => amount,
+                ClaimShare::Ratio(ratio) => {
+                    ratio_amount.multiply_ratio(ratio.numerator(), total_ratio.numerator())
+                }
+            };
+            (r.address.to_string(), share)
+        })
+        .collect();
+
+    let distributed: Uint128 = shares.iter().map(|(_, share)| *share).sum();
+    shares[0].1 += amount.checked_sub(distributed)?;
+
+    Ok(shares)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -44,6 +544,20 @@ pub struct StakingInfo {
     pub reward_contract: String,
 }
 
+/// CompoundConfig is used to reinvest uusd staking rewards into
+/// the vesting position. Rewards are swapped to uluna through
+/// Terra's native market swap, or `pair_contract` when given,
+/// and bonded to bLUNA.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CompoundConfig {
+    /// Terraswap style uusd-uluna pair contract
+    pub pair_contract: Option<String>,
+    /// Add the compounded bLUNA to the vesting schedule,
+    /// otherwise it is vested immediately
+    pub follow_schedule: bool,
+}
+
 /// VestingSchedule is used to vest tokens
 /// at regular intervals for a specific period.
 /// To minimize calculation error,
@@ -57,47 +571,41 @@ pub struct VestingSchedule {
 }
 
 impl VestingSchedule {
-    pub fn validate(&self, block_time: u64, vesting_amount: Uint128) -> StdResult<()> {
+    pub fn validate(&self, block_time: u64, vesting_amount: Uint128) -> Result<(), ContractError> {
         if vesting_amount.is_zero() {
-            return Err(StdError::generic_err(
-                "cannot make zero token vesting account",
-            ));
+            return Err(ContractError::ZeroVestingAmount {});
         }
 
         let start_time = self
             .start_time
             .parse::<u64>()
-            .map_err(|_| StdError::generic_err("invalid start_time"))?;
+            .map_err(|_| ContractError::InvalidStartTime {})?;
 
         let end_time = self
             .end_time
             .parse::<u64>()
-            .map_err(|_| StdError::generic_err("invalid end_time"))?;
+            .map_err(|_| ContractError::InvalidEndTime {})?;
 
         let vesting_interval = self
             .vesting_interval
             .parse::<u64>()
-            .map_err(|_| StdError::generic_err("invalid vesting_interval"))?;
+            .map_err(|_| ContractError::InvalidVestingInterval {})?;
 
         if start_time < block_time {
-            return Err(StdError::generic_err("invalid start_time"));
+            return Err(ContractError::InvalidStartTime {});
         }
 
         if end_time < start_time {
-            return Err(StdError::generic_err(
-                "end_time must be bigger than start_time",
-            ));
+            return Err(ContractError::EndTimeBeforeStartTime {});
         }
 
         if vesting_interval == 0 {
-            return Err(StdError::generic_err("vesting_interval must be non-zero"));
+            return Err(ContractError::ZeroVestingInterval {});
         }
 
         let time_period = end_time - start_time;
         if time_period != (time_period / vesting_interval) * vesting_interval {
-            return Err(StdError::generic_err(
-                "(end_time - start_time) must be multiple of vesting_interval",
-            ));
+            return Err(ContractError::UnalignedVestingPeriod {});
         }
 
         Ok(())
@@ -120,6 +628,127 @@ impl VestingSchedule {
         let vesting_ratio = Decimal::from_ratio(1u64, num_intervals);
         Ok((vesting_ratio * vesting_amount).checked_mul(Uint128::from(passed_intervals))?)
     }
+
+    /// The same schedule started `offset` seconds later
+    pub fn shifted(&self, offset: u64) -> StdResult<VestingSchedule> {
+        let start_time = self.start_time.parse::<u64>().unwrap();
+        let end_time = self.end_time.parse::<u64>().unwrap();
+
+        Ok(VestingSchedule {
+            start_time: Uint64::from(start_time)
+                .checked_add(offset.into())?
+                .to_string(),
+            end_time: Uint64::from(end_time)
+                .checked_add(offset.into())?
+                .to_string(),
+            vesting_interval: self.vesting_interval.clone(),
+        })
+    }
+
+    /// Times after `start_after` at which `vested_amount` increases:
+    /// every `vesting_interval` from `start_time`, up to `end_time`
+    pub fn unlock_times(&self, start_after: Option<u64>) -> impl Iterator<Item = u64> {
+        let start_time = self.start_time.parse::<u64>().unwrap();
+        let end_time = self.end_time.parse::<u64>().unwrap();
+        let vesting_interval = self.vesting_interval.parse::<u64>().unwrap();
+
+        // nothing unlocks after end_time
+        let first_interval = match start_after {
+            Some(time) if time >= end_time => None,
+            Some(time) if time >= start_time => Some((time - start_time) / vesting_interval + 1),
+            _ => Some(0),
+        };
+
+        first_interval.into_iter().flat_map(move |first_interval| {
+            (first_interval..)
+                .map_while(move |interval| {
+                    interval
+                        .checked_mul(vesting_interval)
+                        .and_then(|offset| start_time.checked_add(offset))
+                })
+                .take_while(move |time| *time < end_time)
+                .chain(std::iter::once(end_time))
+        })
+    }
+}
+
+/// Principal of a vesting account held in the vesting pool
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingAccount {
+    pub vesting_denom: Denom,
+    pub vesting_amount: Uint128,
+    pub vesting_schedule: VestingSchedule,
+    pub claimed_amount: Uint128,
+    /// Hub exchange rate when bLUNA was bonded
+    pub bond_exchange_rate: Option<Decimal>,
+}
+
+impl VestingAccount {
+    /// Amount vested at `block_time` and not claimed yet
+    pub fn claimable_amount(&self, block_time: u64) -> StdResult<Uint128> {
+        Ok(self
+            .vesting_schedule
+            .vested_amount(block_time, self.vesting_amount)?
+            .checked_sub(self.claimed_amount)?)
+    }
+
+    /// Principal not claimed yet, vested or not
+    pub fn remaining_amount(&self) -> StdResult<Uint128> {
+        Ok(self.vesting_amount.checked_sub(self.claimed_amount)?)
+    }
+}
+
+/// Make native or cw20 transfer message depends on `denom`.
+/// Native tokens are sent with the stability tax deducted.
+pub fn transfer_message(
+    querier: &dyn TaxQuerier,
+    denom: &Denom,
+    recipient: String,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match denom {
+        Denom::Native(denom) => BankMsg::Send {
+            to_address: recipient,
+            amount: vec![deduct_tax(
+                querier,
+                Coin {
+                    denom: denom.to_string(),
+                    amount,
+                },
+            )?],
+        }
+        .into(),
+        Denom::Cw20(contract_addr) => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient, amount })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Compute the amount to be claimed out of `claimable_amount`.
+/// When `amount` is not given, the whole claimable amount is claimed.
+pub fn claim_amount(
+    claimable_amount: Uint128,
+    amount: Option<Uint128>,
+) -> Result<Uint128, ContractError> {
+    if claimable_amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    // claim only the requested part, the rest stays claimable
+    let claim_amount = amount.unwrap_or(claimable_amount);
+    if claim_amount.is_zero() {
+        return Err(ContractError::ZeroClaimAmount {});
+    }
+
+    if claim_amount > claimable_amount {
+        return Err(ContractError::ClaimAmountExceeded {});
+    }
+
+    Ok(claim_amount)
 }
 
 #[test]
@@ -148,3 +777,166 @@ fn vested_amount() {
         Uint128::new(1000000u128)
     );
 }
+
+#[test]
+fn unlock_times() {
+    let schedule = VestingSchedule {
+        start_time: "100".to_string(),
+        end_time: "115".to_string(),
+        vesting_interval: "5".to_string(),
+    };
+
+    assert_eq!(
+        schedule.unlock_times(None).collect::<Vec<u64>>(),
+        vec![100, 105, 110, 115]
+    );
+    assert_eq!(
+        schedule.unlock_times(Some(99)).collect::<Vec<u64>>(),
+        vec![100, 105, 110, 115]
+    );
+    assert_eq!(
+        schedule.unlock_times(Some(100)).collect::<Vec<u64>>(),
+        vec![105, 110, 115]
+    );
+    assert_eq!(
+        schedule.unlock_times(Some(112)).collect::<Vec<u64>>(),
+        vec![115]
+    );
+    assert_eq!(schedule.unlock_times(Some(115)).count(), 0);
+    assert_eq!(schedule.unlock_times(Some(u64::MAX - 3)).count(), 0);
+
+    // no overflow for schedules ending near u64::MAX
+    let late_schedule = VestingSchedule {
+        start_time: (u64::MAX - 10).to_string(),
+        end_time: u64::MAX.to_string(),
+        vesting_interval: "5".to_string(),
+    };
+    assert_eq!(
+        late_schedule
+            .unlock_times(Some(u64::MAX - 3))
+            .collect::<Vec<u64>>(),
+        vec![u64::MAX]
+    );
+
+    // vested amount changes exactly at unlock times
+    let vesting_amount = Uint128::new(1000000u128);
+    for time in schedule.unlock_times(None) {
+        assert!(
+            schedule.vested_amount(time - 1, vesting_amount).unwrap()
+                < schedule.vested_amount(time, vesting_amount).unwrap()
+        );
+    }
+}
+
+#[test]
+fn shifted_schedule() {
+    let schedule = VestingSchedule {
+        start_time: "0".to_string(),
+        end_time: "300".to_string(),
+        vesting_interval: "100".to_string(),
+    };
+
+    let shifted = schedule.shifted(1000).unwrap();
+    assert_eq!(shifted.start_time, "1000");
+    assert_eq!(shifted.end_time, "1300");
+    assert_eq!(shifted.vesting_interval, "100");
+    assert!(schedule.shifted(u64::MAX).is_err());
+}
+
+#[test]
+fn split_claim_amount() {
+    let recipients = vec![
+        ClaimRecipient {
+            address: "addr0000".to_string(),
+            share: ClaimShare::Ratio(Decimal::from_ratio(1u64, 3u64)),
+        },
+        ClaimRecipient {
+            address: "addr0001".to_string(),
+            share: ClaimShare::Amount(Uint128::new(100u128)),
+        },
+        ClaimRecipient {
+            address: "addr0002".to_string(),
+            share: ClaimShare::Ratio(Decimal::one() - Decimal::from_ratio(1u64, 3u64)),
+        },
+    ];
+
+    // 900 is left for ratio shares, 1/3 of it is 299.999.. which rounds down
+    assert_eq!(fixed_claim_amount(&recipients), None);
+    assert_eq!(
+        split_claim(Uint128::new(1000u128), &recipients).unwrap(),
+        vec![
+            ("addr0000".to_string(), Uint128::new(300u128)),
+            ("addr0001".to_string(), Uint128::new(100u128)),
+            ("addr0002".to_string(), Uint128::new(600u128)),
+        ]
+    );
+
+    // fixed amounts exceed the claim amount
+    assert_eq!(
+        split_claim(Uint128::new(99u128), &recipients),
+        Err(ContractError::RecipientAmountsExceeded {})
+    );
+
+    // ratios are weights, so a single ratio takes everything
+    let recipients = vec![ClaimRecipient {
+        address: "addr0000".to_string(),
+        share: ClaimShare::Ratio(Decimal::percent(50)),
+    }];
+    assert_eq!(
+        split_claim(Uint128::new(1000u128), &recipients).unwrap(),
+        vec![("addr0000".to_string(), Uint128::new(1000u128))]
+    );
+
+    // three thirds do not add up to one, the remainder goes to the first
+    let recipients: Vec<ClaimRecipient> = ["addr0000", "addr0001", "addr0002"]
+        .iter()
+        .map(|address| ClaimRecipient {
+            address: address.to_string(),
+            share: ClaimShare::Ratio(Decimal::from_ratio(1u64, 3u64)),
+        })
+        .collect();
+    assert_eq!(
+        split_claim(Uint128::new(1000u128), &recipients).unwrap(),
+        vec![
+            ("addr0000".to_string(), Uint128::new(334u128)),
+            ("addr0001".to_string(), Uint128::new(333u128)),
+            ("addr0002".to_string(), Uint128::new(333u128)),
+        ]
+    );
+
+    // fixed amounts only
+    let recipients = vec![
+        ClaimRecipient {
+            address: "addr0000".to_string(),
+            share: ClaimShare::Amount(Uint128::new(100u128)),
+        },
+        ClaimRecipient {
+            address: "addr0001".to_string(),
+            share: ClaimShare::Amount(Uint128::new(200u128)),
+        },
+    ];
+    assert_eq!(fixed_claim_amount(&recipients), Some(Uint128::new(300u128)));
+    assert_eq!(
+        split_claim(Uint128::new(301u128), &recipients),
+        Err(ContractError::RecipientAmountsMismatch {})
+    );
+}
+
+#[test]
+fn vesting_account_amounts() {
+    let account = VestingAccount {
+        vesting_denom: Denom::Native("uluna".to_string()),
+        vesting_amount: Uint128::new(1000u128),
+        vesting_schedule: VestingSchedule {
+            start_time: "105".to_string(),
+            end_time: "110".to_string(),
+            vesting_interval: "5".to_string(),
+        },
+        claimed_amount: Uint128::new(300u128),
+        bond_exchange_rate: None,
+    };
+
+    assert_eq!(account.claimable_amount(105), Ok(Uint128::new(200u128)));
+    assert_eq!(account.claimable_amount(110), Ok(Uint128::new(700u128)));
+    assert_eq!(account.remaining_amount(), Ok(Uint128::new(700u128)));
+}
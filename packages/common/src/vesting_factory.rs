@@ -1,4 +1,4 @@
-use crate::vesting::VestingSchedule;
+use crate::vesting::{BeneficiaryShare, SolvencyResponse, VestingSchedule};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +8,12 @@ pub struct InstantiateMsg {
     pub hub_contract: String,
     pub reward_contract: String,
     pub vesting_contract_code_id: u64,
+    /// When given, vesting accounts are created in this vesting pool
+    /// instead of instantiating a new vesting contract per account
+    pub vesting_pool: Option<String>,
+    /// Allowed to start, suspend and resume vesting contracts,
+    /// the sender by default
+    pub admin: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -17,16 +23,45 @@ pub enum ExecuteMsg {
         owner_address: String,
         enable_staking: bool,
         vesting_schedule: VestingSchedule,
+        beneficiaries: Option<Vec<BeneficiaryShare>>,
+        /// When set, `vesting_schedule` is relative to the start
+        /// triggered later with `StartVesting`
+        deferred_start: Option<bool>,
     },
     ChangeVestingOwner {
         new_owner: String,
     },
+    /// Start up to `limit` pending vesting contracts at the current
+    /// block time, executable by the admin only
+    StartVesting {
+        limit: Option<u32>,
+    },
+    /// Suspend the vesting contract of the owner, executable by the admin only
+    Suspend {
+        owner_address: String,
+    },
+    /// Resume the vesting contract of the owner, executable by the admin only
+    Resume {
+        owner_address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    VestingContract { owner_address: String },
+    VestingContract {
+        owner_address: String,
+    },
+    /// Solvency of the registered vesting contracts, ordered by owner
+    Solvency {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Vesting contracts waiting for `StartVesting`, ordered by address
+    PendingStarts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
@@ -34,3 +69,23 @@ pub struct VestingContractResponse {
     pub owner_address: String,
     pub vesting_contract: String,
 }
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct PendingStartsResponse {
+    pub vesting_contracts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct SolvenciesResponse {
+    pub contracts: Vec<ContractSolvencyResponse>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct ContractSolvencyResponse {
+    pub owner_address: String,
+    pub vesting_contract: String,
+    /// `None` for accounts in the vesting pool or when the query failed
+    pub solvency: Option<SolvencyResponse>,
+    /// Error of the vesting contract, in which case `solvency` is `None`
+    pub error: Option<String>,
+}
@@ -0,0 +1,68 @@
+use crate::vesting::{StakingInfo, VestingInfoResponse, VestingSchedule};
+use cosmwasm_std::{Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Account allowed to create vesting accounts, usually the factory
+    pub admin: String,
+    /// Required to create staking enabled vesting accounts
+    pub staking_info: Option<StakingInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdateAdmin {
+        admin: String,
+    },
+    CreateAccount {
+        owner_address: String,
+        enable_staking: bool,
+        vesting_schedule: VestingSchedule,
+    },
+    ChangeOwner {
+        new_owner: String,
+    },
+    Claim {
+        recipient: Option<String>,
+        amount: Option<Uint128>,
+    },
+    ClaimRewards {
+        recipient: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+    VestingAccount {
+        owner_address: String,
+    },
+    VestingAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct ConfigResponse {
+    pub admin: String,
+    pub staking_info: Option<StakingInfo>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct StateResponse {
+    /// Unclaimed bLUNA of all staking enabled accounts
+    pub total_staked: Uint128,
+    /// Accumulated rewards per staked bLUNA
+    pub reward_index: Decimal,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, PartialEq, Debug)]
+pub struct VestingAccountsResponse {
+    pub vesting_accounts: Vec<VestingInfoResponse>,
+}